@@ -14,8 +14,15 @@ pub mod contract_7 {
         sensor_data.readings = Vec::new();
         sensor_data.image_data = Vec::new();
         sensor_data.machine_id = machine_id;
+        sensor_data.owner = ctx.accounts.user.key();
+        sensor_data.operator = None;
         sensor_data.total_readings = 0;
+        sensor_data.total_images = 0;
+        sensor_data.last_measured_at = 0;
+        sensor_data.max_clock_skew_secs = SensorData::DEFAULT_MAX_CLOCK_SKEW_SECS;
+        sensor_data.max_backfill_secs = SensorData::DEFAULT_MAX_BACKFILL_SECS;
         sensor_data.is_on = false;
+        sensor_data.delegation = DelegationRecord::default();
         msg!("Sensor Data Account initialized for machine: {}", sensor_data.machine_id);
         Ok(())
     }
@@ -44,20 +51,114 @@ pub mod contract_7 {
         Ok(())
     }
 
-    /// Delegate the account to the delegation program
-    pub fn delegate(ctx: Context<DelegateInput>) -> Result<()> {
-        ctx.accounts.delegate_pda(
+    /// Allow an operator key (e.g. the sensor device) to add data and images,
+    /// or remove it with `None`
+    pub fn set_operator(ctx: Context<SetOperator>, operator: Option<Pubkey>) -> Result<()> {
+        let sensor_data = &mut ctx.accounts.sensor_data;
+        sensor_data.operator = operator;
+
+        msg!("Machine {} operator set to: {:?}", sensor_data.machine_id, operator);
+        Ok(())
+    }
+
+    /// Let `session_key` add data and images in `scope` until the session
+    /// expires, without making it the operator
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        duration_secs: i64,
+        scope: u8,
+    ) -> Result<()> {
+        if duration_secs <= 0 || duration_secs > SessionToken::MAX_DURATION_SECS {
+            return Err(error!(ErrorCode::InvalidSessionDuration));
+        }
+        if scope == 0 || scope & !SessionToken::ALL_SCOPES != 0 {
+            return Err(error!(ErrorCode::InvalidSessionScope));
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let session = &mut ctx.accounts.session;
+        session.sensor_data = ctx.accounts.sensor_data.key();
+        session.owner = ctx.accounts.owner.key();
+        session.session_key = session_key;
+        session.expires_at = now + duration_secs;
+        session.scope = scope;
+        session.bump = ctx.bumps.session;
+
+        msg!("Session {} created for machine {}", session_key, ctx.accounts.sensor_data.machine_id);
+        Ok(())
+    }
+
+    /// Revoke a session and return its rent to the owner
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        msg!("Session {} revoked", ctx.accounts.session.session_key);
+        Ok(())
+    }
+
+    /// Set how far ahead of the chain clock and how far in the past a
+    /// device-supplied measurement time may be. A backfill window of 0
+    /// accepts readings of any age.
+    pub fn set_time_window(
+        ctx: Context<SetTimeWindow>,
+        max_clock_skew_secs: i64,
+        max_backfill_secs: i64,
+    ) -> Result<()> {
+        if max_clock_skew_secs < 0 || max_backfill_secs < 0 {
+            return Err(error!(ErrorCode::InvalidTimeWindow));
+        }
+
+        let sensor_data = &mut ctx.accounts.sensor_data;
+        sensor_data.max_clock_skew_secs = max_clock_skew_secs;
+        sensor_data.max_backfill_secs = max_backfill_secs;
+
+        msg!(
+            "Machine {} time window set: {}s skew, {}s backfill",
+            sensor_data.machine_id,
+            max_clock_skew_secs,
+            max_backfill_secs
+        );
+        Ok(())
+    }
+
+    /// Delegate the sensor data to the ephemeral rollup. Only the owner can
+    /// delegate it.
+    pub fn delegate(
+        ctx: Context<DelegateInput>,
+        validator: Option<Pubkey>,
+        commit_frequency_ms: u32,
+    ) -> Result<()> {
+        let record = DelegationRecord::new(validator, commit_frequency_ms, Clock::get()?.unix_timestamp)?;
+
+        // Persist the settings before the account changes owner
+        ctx.accounts.sensor_data.delegation = record;
+        ctx.accounts.sensor_data.exit(&crate::ID)?;
+
+        ctx.accounts.delegate_sensor_data(
             &ctx.accounts.payer,
             &[b"machine", ctx.accounts.sensor_data.machine_id.as_bytes()],  
-            DelegateConfig::default(),
+            record.config(),
         )?;
         Ok(())
     }
 
+    /// Checkpoint the delegated sensor data to the base layer without
+    /// undelegating it
+    pub fn commit(ctx: Context<CommitSensorData>) -> Result<()> {
+        commit_accounts(
+            &ctx.accounts.payer,
+            vec![&ctx.accounts.sensor_data.to_account_info()],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
+        msg!("Machine {} committed", ctx.accounts.sensor_data.machine_id);
+        Ok(())
+    }
+
+    /// Commit and undelegate the sensor data. Only the owner can undelegate it.
     pub fn undelegate(ctx: Context<UndelegateAccounts>) -> Result<()> {
         commit_and_undelegate_accounts(
             &ctx.accounts.payer,
-            vec![&ctx.accounts.pda.to_account_info()],
+            vec![&ctx.accounts.sensor_data.to_account_info()],
             &ctx.accounts.magic_context,
             &ctx.accounts.magic_program,
         )?;
@@ -68,7 +169,10 @@ pub mod contract_7 {
         ctx: Context<AddData>, 
         temperature_c: f32,
         humidity: f32,
+        measured_at: Option<i64>,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        check_signer(ctx.accounts, SessionToken::SCOPE_ADD_DATA, now)?;
         let sensor_data = &mut ctx.accounts.sensor_data;
 
         // Validate machine is turned on
@@ -76,16 +180,33 @@ pub mod contract_7 {
             return Err(error!(ErrorCode::MachineNotOn));
         }
 
+        // Validate the device-supplied measurement time
+        if let Some(measured_at) = measured_at {
+            if measured_at > now.saturating_add(sensor_data.max_clock_skew_secs) {
+                return Err(error!(ErrorCode::MeasurementInFuture));
+            }
+            if sensor_data.max_backfill_secs > 0
+                && measured_at < now.saturating_sub(sensor_data.max_backfill_secs)
+            {
+                return Err(error!(ErrorCode::MeasurementTooOld));
+            }
+            if measured_at <= sensor_data.last_measured_at {
+                return Err(error!(ErrorCode::MeasurementNotIncreasing));
+            }
+        }
+        // Later device times must also follow readings stamped with the chain clock
+        sensor_data.last_measured_at = sensor_data.last_measured_at.max(measured_at.unwrap_or(now));
+
         // Create new reading
         let new_reading = SensorReading {
             temperature_c,
             humidity,
-            timestamp: Clock::get()?.unix_timestamp,
+            timestamp: now,
+            measured_at,
         };
 
-        // Add the reading to the vector
-        sensor_data.readings.push(new_reading);
-        sensor_data.total_readings += 1;
+        // Add the reading, evicting the oldest once the buffer is full
+        sensor_data.push_reading(new_reading);
 
         msg!("New Sensor Reading added!");
         msg!("Machine ID: {}", sensor_data.machine_id);
@@ -98,8 +219,10 @@ pub mod contract_7 {
 
     pub fn add_image(
         ctx: Context<AddData>,
-        image_uri: String,
+        image: ImageRef,
     ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        check_signer(ctx.accounts, SessionToken::SCOPE_ADD_IMAGE, now)?;
         let sensor_data = &mut ctx.accounts.sensor_data;
 
         // Validate machine is turned on
@@ -107,28 +230,39 @@ pub mod contract_7 {
             return Err(error!(ErrorCode::MachineNotOn));
         }
 
-        // Validate input lengths
-        if image_uri.len() > SensorData::MAX_URI_LENGTH {
-            return Err(error!(ErrorCode::UriTooLong));
-        }
+        // Validate the image reference
+        image.validate()?;
 
         // Create new image data
         let new_image = ImageData {
-            image_uri: image_uri.clone(),
-            timestamp: Clock::get()?.unix_timestamp,
+            image: image.clone(),
+            timestamp: now,
         };
 
-        // Add the image data to the vector
-        sensor_data.image_data.push(new_image);
+        // Add the image data, evicting the oldest once the buffer is full
+        sensor_data.push_image(new_image);
 
         msg!("New Image Data added!");
         msg!("Machine ID: {}", sensor_data.machine_id);
-        msg!("Image URI: {}", image_uri);
+        msg!("Image storage: {:?}", image.storage);
         
         Ok(())
     }
 }
 
+/// The owner, the operator or the key of a live session covering `scope`
+/// may add data and images
+fn check_signer(accounts: &AddData, scope: u8, now: i64) -> Result<()> {
+    if accounts.sensor_data.can_operate(&accounts.user.key()) {
+        return Ok(());
+    }
+
+    match &accounts.session {
+        Some(session) => session.check(scope, now),
+        None => Err(error!(ErrorCode::Unauthorized)),
+    }
+}
+
 #[derive(Accounts)]
 #[instruction(machine_id: String)]
 pub struct Initialize<'info> {
@@ -136,7 +270,7 @@ pub struct Initialize<'info> {
         init,
         payer = user,
         space = 8 + SensorData::MAX_SIZE,
-        seeds = [b"machine", machine_id.as_bytes()],
+        seeds = [b"machine", SensorData::machine_id_seed(&machine_id)?],
         bump
     )]
     pub sensor_data: Account<'info, SensorData>,
@@ -154,6 +288,46 @@ pub struct AddData<'info> {
     )]
     pub sensor_data: Account<'info, SensorData>,
     pub user: Signer<'info>,
+    /// Session letting `user` add data without being the owner or operator
+    #[account(
+        seeds = [b"session", sensor_data.key().as_ref(), user.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionToken>>,
+}
+
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(
+        seeds = [b"machine", sensor_data.machine_id.as_bytes()],
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub sensor_data: Account<'info, SensorData>,
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + SessionToken::MAX_SIZE,
+        seeds = [b"session", sensor_data.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionToken>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub session: Account<'info, SessionToken>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
 }
 
 #[derive(Accounts)]
@@ -161,10 +335,35 @@ pub struct TogglePower<'info> {
     #[account(
         mut,
         seeds = [b"machine", sensor_data.machine_id.as_bytes()],
-        bump
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized
     )]
     pub sensor_data: Account<'info, SensorData>,
-    pub user: Signer<'info>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperator<'info> {
+    #[account(
+        mut,
+        seeds = [b"machine", sensor_data.machine_id.as_bytes()],
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub sensor_data: Account<'info, SensorData>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTimeWindow<'info> {
+    #[account(
+        mut,
+        seeds = [b"machine", sensor_data.machine_id.as_bytes()],
+        bump,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub sensor_data: Account<'info, SensorData>,
+    pub owner: Signer<'info>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -172,71 +371,288 @@ pub struct SensorReading {
     pub temperature_c: f32,
     pub humidity: f32,
     pub timestamp: i64,
+    pub measured_at: Option<i64>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ImageData {
-    pub image_uri: String,
+    pub image: ImageRef,
     pub timestamp: i64,
 }
 
+/// Where an image is stored. Buyers verify the downloaded bytes against
+/// `ImageRef.content_hash`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ImageStorage {
+    /// IPFS content identifier
+    Ipfs(String),
+    /// Arweave transaction id
+    Arweave([u8; 32]),
+    /// HTTPS URL
+    Https(String),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImageMime {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+/// Content-addressed reference to an image captured by the machine
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ImageRef {
+    pub storage: ImageStorage,
+    /// SHA-256 of the image bytes
+    pub content_hash: [u8; 32],
+    pub mime: ImageMime,
+    /// Image size in bytes
+    pub size: u32,
+}
+
+impl ImageRef {
+    // 1 byte (storage variant) + (4 + MAX_URI_LENGTH) bytes (largest locator) +
+    // 32 bytes (content_hash) + 1 byte (mime) + 4 bytes (size)
+    pub const SPACE: usize = 1 + (4 + SensorData::MAX_URI_LENGTH) + 32 + 1 + 4;
+
+    pub fn validate(&self) -> Result<()> {
+        match &self.storage {
+            ImageStorage::Ipfs(cid) => {
+                if cid.is_empty() {
+                    return Err(error!(ErrorCode::InvalidImageRef));
+                }
+                if cid.len() > SensorData::MAX_URI_LENGTH {
+                    return Err(error!(ErrorCode::UriTooLong));
+                }
+            }
+            ImageStorage::Arweave(_) => {}
+            ImageStorage::Https(url) => {
+                if !url.starts_with("https://") {
+                    return Err(error!(ErrorCode::InvalidImageRef));
+                }
+                if url.len() > SensorData::MAX_URI_LENGTH {
+                    return Err(error!(ErrorCode::UriTooLong));
+                }
+            }
+        }
+
+        if self.size == 0 || self.content_hash == [0; 32] {
+            return Err(error!(ErrorCode::InvalidImageRef));
+        }
+        Ok(())
+    }
+}
+
+/// `readings` and `image_data` are ring buffers: once full, the entry at
+/// `total_readings % MAX_READINGS` (resp. `total_images % MAX_IMAGES`) is the
+/// oldest and is overwritten next.
 #[account]
 #[derive(Default)]
 pub struct SensorData {
     pub readings: Vec<SensorReading>,
     pub image_data: Vec<ImageData>,
     pub machine_id: String,
+    pub owner: Pubkey,
+    pub operator: Option<Pubkey>,
     pub total_readings: u64,
+    pub total_images: u64,
+    /// Latest reading time, device-supplied or from the chain clock
+    pub last_measured_at: i64,
+    pub max_clock_skew_secs: i64,
+    pub max_backfill_secs: i64,
     pub is_on: bool,
+    pub delegation: DelegationRecord,
 }
 
 impl SensorData {
     pub const MAX_URI_LENGTH: usize = 100;
-    pub const MAX_MACHINE_ID_LENGTH: usize = 50;
+    // The machine ID is a PDA seed, which is limited to 32 bytes
+    pub const MAX_MACHINE_ID_LENGTH: usize = 32;
     pub const MAX_READINGS: usize = 50;
     pub const MAX_IMAGES: usize = 40;
+    pub const DEFAULT_MAX_CLOCK_SKEW_SECS: i64 = 60;
+    pub const DEFAULT_MAX_BACKFILL_SECS: i64 = 7 * 24 * 60 * 60;
     
     // Calculate total space needed:
     // 8 bytes (discriminator) +
     // (4 + MAX_MACHINE_ID_LENGTH) bytes (String) +
+    // 32 bytes (owner) +
+    // 33 bytes (operator Option<Pubkey>) +
     // 8 bytes (total_readings) +
+    // 8 bytes (total_images) +
+    // 8 bytes (last_measured_at) +
+    // 8 bytes (max_clock_skew_secs) +
+    // 8 bytes (max_backfill_secs) +
     // 1 byte (is_on boolean) +
+    // DelegationRecord::SPACE bytes (delegation) +
     // Vec<SensorReading> space:
     //   - 4 bytes (vec len) +
     //   - MAX_READINGS * (
     //     4 bytes (f32) + 
     //     4 bytes (f32) + 
-    //     8 bytes (i64)
+    //     8 bytes (i64) +
+    //     9 bytes (Option<i64>)
     //   ) +
     // Vec<ImageData> space:
     //   - 4 bytes (vec len) +
     //   - MAX_IMAGES * (
-    //     (4 + MAX_URI_LENGTH) bytes (String) +
+    //     ImageRef::SPACE bytes +
     //     8 bytes (i64)
     //   )
-    pub const MAX_SIZE: usize = 8 + (4 + SensorData::MAX_MACHINE_ID_LENGTH) + 8 + 1 +
-        4 + (SensorData::MAX_READINGS * (4 + 4 + 8)) +
-        4 + (SensorData::MAX_IMAGES * ((4 + SensorData::MAX_URI_LENGTH) + 8));
+    pub const MAX_SIZE: usize = 8 + (4 + SensorData::MAX_MACHINE_ID_LENGTH) + 32 + 33 + 8 + 8 + 8 + 8 + 8 + 1 +
+        DelegationRecord::SPACE +
+        4 + (SensorData::MAX_READINGS * (4 + 4 + 8 + 9)) +
+        4 + (SensorData::MAX_IMAGES * (ImageRef::SPACE + 8));
+
+    /// Check a new machine ID and return it as a PDA seed for `Initialize`
+    pub fn machine_id_seed(machine_id: &str) -> Result<&[u8]> {
+        if machine_id.is_empty() {
+            return Err(error!(ErrorCode::EmptyMachineId));
+        }
+        if machine_id.len() > Self::MAX_MACHINE_ID_LENGTH {
+            return Err(error!(ErrorCode::MachineIdTooLong));
+        }
+        Ok(machine_id.as_bytes())
+    }
+
+    pub fn push_reading(&mut self, reading: SensorReading) {
+        if self.readings.len() < Self::MAX_READINGS {
+            self.readings.push(reading);
+        } else {
+            let oldest = (self.total_readings % Self::MAX_READINGS as u64) as usize;
+            self.readings[oldest] = reading;
+        }
+        self.total_readings += 1;
+    }
+
+    pub fn push_image(&mut self, image: ImageData) {
+        if self.image_data.len() < Self::MAX_IMAGES {
+            self.image_data.push(image);
+        } else {
+            let oldest = (self.total_images % Self::MAX_IMAGES as u64) as usize;
+            self.image_data[oldest] = image;
+        }
+        self.total_images += 1;
+    }
+
+    /// The owner and the optional operator may add data and images
+    pub fn can_operate(&self, key: &Pubkey) -> bool {
+        *key == self.owner || self.operator.as_ref() == Some(key)
+    }
+}
+
+/// Short-lived key allowed to add data and images for a machine, derived
+/// from `[b"session", sensor_data, session_key]`
+#[account]
+pub struct SessionToken {
+    pub sensor_data: Pubkey,
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub expires_at: i64,
+    pub scope: u8,
+    pub bump: u8,
+}
+
+impl SessionToken {
+    pub const SCOPE_ADD_DATA: u8 = 1 << 0;
+    pub const SCOPE_ADD_IMAGE: u8 = 1 << 1;
+    pub const ALL_SCOPES: u8 = Self::SCOPE_ADD_DATA | Self::SCOPE_ADD_IMAGE;
+    pub const MAX_DURATION_SECS: i64 = 30 * 24 * 60 * 60;
+
+    // 32 bytes (sensor_data) + 32 bytes (owner) + 32 bytes (session_key) +
+    // 8 bytes (expires_at) + 1 byte (scope) + 1 byte (bump)
+    pub const MAX_SIZE: usize = 32 + 32 + 32 + 8 + 1 + 1;
+
+    pub fn check(&self, scope: u8, now: i64) -> Result<()> {
+        if now >= self.expires_at {
+            return Err(error!(ErrorCode::SessionExpired));
+        }
+        if self.scope & scope != scope {
+            return Err(error!(ErrorCode::SessionScopeNotAllowed));
+        }
+        Ok(())
+    }
+}
+
+/// Ephemeral rollup settings the account was last delegated with, kept for
+/// auditing
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct DelegationRecord {
+    pub validator: Option<Pubkey>,
+    pub commit_frequency_ms: u32,
+    pub delegated_at: i64,
+}
+
+impl DelegationRecord {
+    // 33 bytes (validator Option<Pubkey>) + 4 bytes (commit_frequency_ms) +
+    // 8 bytes (delegated_at)
+    pub const SPACE: usize = 33 + 4 + 8;
+    pub const MIN_COMMIT_FREQUENCY_MS: u32 = 1_000;
+    pub const MAX_COMMIT_FREQUENCY_MS: u32 = 60 * 60 * 1_000;
+
+    pub fn new(validator: Option<Pubkey>, commit_frequency_ms: u32, now: i64) -> Result<Self> {
+        if validator == Some(Pubkey::default()) {
+            return Err(error!(ErrorCode::InvalidDelegationValidator));
+        }
+        if !(Self::MIN_COMMIT_FREQUENCY_MS..=Self::MAX_COMMIT_FREQUENCY_MS).contains(&commit_frequency_ms) {
+            return Err(error!(ErrorCode::InvalidCommitFrequency));
+        }
+
+        Ok(Self {
+            validator,
+            commit_frequency_ms,
+            delegated_at: now,
+        })
+    }
+
+    pub fn config(&self) -> DelegateConfig {
+        DelegateConfig {
+            commit_frequency_ms: self.commit_frequency_ms,
+            validator: self.validator,
+        }
+    }
 }
 
 #[delegate]
 #[derive(Accounts)]
 pub struct DelegateInput<'info> {
     pub payer: Signer<'info>,
-    /// CHECK The pda to delegate
-    #[account(mut, del)]
-    pub pda: AccountInfo<'info>,
+    #[account(
+        mut,
+        del,
+        seeds = [b"machine", sensor_data.machine_id.as_bytes()],
+        bump,
+        constraint = sensor_data.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
     pub sensor_data: Account<'info, SensorData>,
 }
 
 #[commit]
 #[derive(Accounts)]
-pub struct UndelegateAccounts<'info> {
+pub struct CommitSensorData<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    /// CHECK: The PDA to undelegate
+    #[account(
+        mut,
+        seeds = [b"machine", sensor_data.machine_id.as_bytes()],
+        bump,
+        constraint = sensor_data.can_operate(&payer.key()) @ ErrorCode::Unauthorized
+    )]
+    pub sensor_data: Account<'info, SensorData>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateAccounts<'info> {
     #[account(mut)]
-    pub pda: AccountInfo<'info>,
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"machine", sensor_data.machine_id.as_bytes()],
+        bump,
+        constraint = sensor_data.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub sensor_data: Account<'info, SensorData>,
 }
 
 #[error_code]
@@ -251,4 +667,93 @@ pub enum ErrorCode {
     MachineAlreadyOff,
     #[msg("Machine must be turned on to perform this action")]
     MachineNotOn,
+    #[msg("Measurement time is in the future")]
+    MeasurementInFuture,
+    #[msg("Measurement time is older than the backfill window")]
+    MeasurementTooOld,
+    #[msg("Measurement time must be after the previous reading")]
+    MeasurementNotIncreasing,
+    #[msg("Invalid image reference")]
+    InvalidImageRef,
+    #[msg("Unauthorized operation")]
+    Unauthorized,
+    #[msg("Delegation validator cannot be the default pubkey")]
+    InvalidDelegationValidator,
+    #[msg("Commit frequency is outside the allowed range")]
+    InvalidCommitFrequency,
+    #[msg("Session duration is outside the allowed range")]
+    InvalidSessionDuration,
+    #[msg("Session scope is empty or contains unknown flags")]
+    InvalidSessionScope,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Session does not allow this instruction")]
+    SessionScopeNotAllowed,
+    #[msg("Clock skew and backfill windows cannot be negative")]
+    InvalidTimeWindow,
+    #[msg("Machine ID cannot be empty")]
+    EmptyMachineId,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn machine_id_seed_accepts_exactly_the_ids_that_derive_a_pda() {
+        for len in 0..=2 * SensorData::MAX_MACHINE_ID_LENGTH {
+            let machine_id = "m".repeat(len);
+            match SensorData::machine_id_seed(&machine_id) {
+                Ok(seed) => {
+                    assert!(len > 0 && len <= SensorData::MAX_MACHINE_ID_LENGTH);
+                    assert!(Pubkey::try_find_program_address(&[b"machine", seed], &crate::ID).is_some());
+                }
+                Err(err) if len == 0 => assert_eq!(err, ErrorCode::EmptyMachineId.into()),
+                Err(err) => {
+                    assert!(len > SensorData::MAX_MACHINE_ID_LENGTH);
+                    assert_eq!(err, ErrorCode::MachineIdTooLong.into());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn full_sensor_data_fits_account_space() {
+        let mut sensor_data = SensorData {
+            machine_id: "m".repeat(SensorData::MAX_MACHINE_ID_LENGTH),
+            operator: Some(Pubkey::new_unique()),
+            delegation: DelegationRecord {
+                validator: Some(Pubkey::new_unique()),
+                commit_frequency_ms: u32::MAX,
+                delegated_at: i64::MAX,
+            },
+            ..Default::default()
+        };
+        for _ in 0..=SensorData::MAX_READINGS {
+            sensor_data.push_reading(SensorReading {
+                temperature_c: f32::MAX,
+                humidity: f32::MAX,
+                timestamp: i64::MAX,
+                measured_at: Some(i64::MAX),
+            });
+        }
+        for _ in 0..=SensorData::MAX_IMAGES {
+            sensor_data.push_image(ImageData {
+                image: ImageRef {
+                    storage: ImageStorage::Https(format!(
+                        "https://{}",
+                        "a".repeat(SensorData::MAX_URI_LENGTH - "https://".len())
+                    )),
+                    content_hash: [1; 32],
+                    mime: ImageMime::Webp,
+                    size: u32::MAX,
+                },
+                timestamp: i64::MAX,
+            });
+        }
+
+        let mut data = Vec::new();
+        sensor_data.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= 8 + SensorData::MAX_SIZE);
+    }
 }
@@ -5,6 +5,7 @@ import { useAgroXContract } from '@/hooks/useAgroXContract';
 export default function Test3Page() {
   const {
     cluster,
+    totalDataUploads,
     // machines,
     plants,
     // iotData,
@@ -38,7 +39,7 @@ export default function Test3Page() {
               <p>Total Plants: {cluster.plantCount.toString()}</p>
             </div>
             <div>
-              <p>Total Data Uploads: {cluster.totalDataUploads.toString()}</p>
+              <p>Total Data Uploads: {totalDataUploads.toString()}</p>
               <p>Data Requests: {cluster.dataRequestCount.toString()}</p>
             </div>
           </div>
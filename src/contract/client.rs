@@ -0,0 +1,46 @@
+//! Helpers for off-chain clients. Readings are stored on chain as fixed-point
+//! integers so that comparisons and aggregates are exact on every client.
+
+use crate::{MAX_HUMIDITY_PERMILLE, MAX_NAME_LENGTH};
+
+/// Temperatures are stored in hundredths of a degree Celsius
+pub const TEMPERATURE_SCALE: f64 = 100.0;
+
+/// Relative humidity is stored in tenths of a percent
+pub const HUMIDITY_SCALE: f64 = 10.0;
+
+/// Convert degrees Celsius to the on-chain encoding. Returns `None` for NaN,
+/// infinite or unrepresentable values.
+pub fn encode_temperature(celsius: f64) -> Option<i16> {
+    let scaled = (celsius * TEMPERATURE_SCALE).round();
+    if !scaled.is_finite() || scaled < i16::MIN as f64 || scaled > i16::MAX as f64 {
+        return None;
+    }
+    Some(scaled as i16)
+}
+
+/// Convert an on-chain temperature back to degrees Celsius
+pub fn decode_temperature(temperature: i16) -> f64 {
+    temperature as f64 / TEMPERATURE_SCALE
+}
+
+/// Convert relative humidity in percent to the on-chain encoding. Returns
+/// `None` for NaN, infinite or out of range values.
+pub fn encode_humidity(percent: f64) -> Option<u16> {
+    let scaled = (percent * HUMIDITY_SCALE).round();
+    if !scaled.is_finite() || scaled < 0.0 || scaled > MAX_HUMIDITY_PERMILLE as f64 {
+        return None;
+    }
+    Some(scaled as u16)
+}
+
+/// Convert an on-chain humidity back to percent
+pub fn decode_humidity(humidity: u16) -> f64 {
+    humidity as f64 / HUMIDITY_SCALE
+}
+
+/// Check a machine ID or plant name before sending it, matching the
+/// program's `name_seed` check
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= MAX_NAME_LENGTH
+}
@@ -1,9 +1,47 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as sysvar_instructions, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::system_program;
 use ephemeral_rollups_sdk::anchor::{commit, delegate, ephemeral};
 use ephemeral_rollups_sdk::cpi::DelegateConfig;
 use ephemeral_rollups_sdk::ephem::{commit_accounts, commit_and_undelegate_accounts};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{self, Mint, MintTo, Token, TokenAccount};
 declare_id!("4oweKJAgekQk5WoixX6Uagk8SNTbpPZb6QhmYd9Vv6nW");
 
+pub mod client;
+pub mod metrics;
+
+/// Rewards are counted in whole tokens, so the mint has no decimals
+pub const REWARD_DECIMALS: u8 = 0;
+
+/// Machine IDs and plant names are used as PDA seeds, which are limited to
+/// 32 bytes
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Check a user-supplied machine ID or plant name and return it as a PDA
+/// seed. Anchor derives `init` addresses before the handler runs, so account
+/// structs call this from their `seeds` to fail with `too_long` instead of
+/// panicking on an over-long seed.
+pub fn name_seed(name: &str, too_long: ErrorCode) -> Result<&[u8]> {
+    require!(!name.is_empty(), ErrorCode::EmptyName);
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(too_long.into());
+    }
+    Ok(name.as_bytes())
+}
+
+/// Protocol fees are expressed in basis points of the data price
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+/// Readings are stored as fixed-point integers: temperature in hundredths of
+/// a degree Celsius and relative humidity in tenths of a percent (per-mille).
+/// See the `client` module for conversions.
+pub const MAX_HUMIDITY_PERMILLE: u16 = 1000;
+
 
 #[ephemeral]
 #[program]
@@ -14,46 +52,394 @@ pub mod agrox_contract {
         let cluster = &mut ctx.accounts.cluster;
         cluster.authority = ctx.accounts.authority.key();
         cluster.machine_count = 0;
-        cluster.total_data_uploads = 0;
         cluster.data_request_count = 0;
         cluster.plant_count = 0;
-        cluster.machines = Vec::new();
-        cluster.plants = Vec::new();
+        cluster.reward_mint = Pubkey::default();
+        cluster.reward_authority_bump = 0;
+        cluster.protocol_fee_bps = 0;
+        cluster.upload_policy = UploadPolicy::default();
+        cluster.sensor_ranges = SensorRanges::default();
+        cluster.delegation = DelegationRecord::default();
         cluster.bump = ctx.bumps.cluster;
 
         msg!("AgroX system initialized by: {}", cluster.authority);
         Ok(())
     }
 
-    /// Delegate the account to the delegation program
-    pub fn delegate(ctx: Context<DelegateInput>) -> Result<()> {
-        ctx.accounts.delegate_pda(
+    /// Create the SPL mint used to pay out machine rewards. The mint authority
+    /// is the `reward_authority` PDA, so only this program can mint.
+    pub fn initialize_reward_mint(ctx: Context<InitializeRewardMint>) -> Result<()> {
+        let cluster = &mut ctx.accounts.cluster;
+        cluster.reward_mint = ctx.accounts.reward_mint.key();
+        cluster.reward_authority_bump = ctx.bumps.reward_authority;
+
+        msg!("Reward mint initialized: {}", cluster.reward_mint);
+        Ok(())
+    }
+
+    /// Create the treasury PDA that collects protocol fees from data sales
+    pub fn initialize_treasury(ctx: Context<InitializeTreasury>, protocol_fee_bps: u16) -> Result<()> {
+        require!(protocol_fee_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidProtocolFee);
+
+        let treasury = &mut ctx.accounts.treasury;
+        treasury.fees_collected = 0;
+        treasury.bump = ctx.bumps.treasury;
+
+        let cluster = &mut ctx.accounts.cluster;
+        cluster.protocol_fee_bps = protocol_fee_bps;
+
+        msg!("Treasury initialized with protocol fee: {} bps", protocol_fee_bps);
+        Ok(())
+    }
+
+    /// Create the rollup config PDA. It is never delegated, so delegate and
+    /// commit instructions can read it on the base layer and in the rollup.
+    pub fn initialize_rollup_config(
+        ctx: Context<InitializeRollupConfig>,
+        max_commit_interval_secs: i64,
+    ) -> Result<()> {
+        require!(max_commit_interval_secs >= 0, ErrorCode::InvalidCommitInterval);
+
+        let rollup_config = &mut ctx.accounts.rollup_config;
+        rollup_config.authority = ctx.accounts.authority.key();
+        rollup_config.max_commit_interval_secs = max_commit_interval_secs;
+        rollup_config.bump = ctx.bumps.rollup_config;
+
+        msg!("Rollup config initialized with max commit interval: {}s", max_commit_interval_secs);
+        Ok(())
+    }
+
+    pub fn set_protocol_fee(ctx: Context<UpdateCluster>, protocol_fee_bps: u16) -> Result<()> {
+        require!(protocol_fee_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidProtocolFee);
+
+        let cluster = &mut ctx.accounts.cluster;
+        cluster.protocol_fee_bps = protocol_fee_bps;
+
+        msg!("Protocol fee set to: {} bps", protocol_fee_bps);
+        Ok(())
+    }
+
+    pub fn set_upload_policy(ctx: Context<UpdateCluster>, upload_policy: UploadPolicy) -> Result<()> {
+        require!(
+            upload_policy.min_upload_interval_secs >= 0
+                && upload_policy.min_image_interval_secs >= 0
+                && upload_policy.max_clock_skew_secs >= 0
+                && upload_policy.max_backfill_secs >= 0,
+            ErrorCode::InvalidUploadPolicy
+        );
+
+        let cluster = &mut ctx.accounts.cluster;
+        cluster.upload_policy = upload_policy;
+
+        msg!(
+            "Upload policy set: {}s between uploads, {}s between images",
+            upload_policy.min_upload_interval_secs,
+            upload_policy.min_image_interval_secs
+        );
+        Ok(())
+    }
+
+    pub fn set_sensor_ranges(ctx: Context<UpdateCluster>, sensor_ranges: SensorRanges) -> Result<()> {
+        require!(sensor_ranges.is_valid(), ErrorCode::InvalidSensorRanges);
+
+        let cluster = &mut ctx.accounts.cluster;
+        cluster.sensor_ranges = sensor_ranges;
+
+        msg!(
+            "Sensor ranges set: {}..{} centi-°C, {}..{} per-mille humidity",
+            sensor_ranges.min_temperature,
+            sensor_ranges.max_temperature,
+            sensor_ranges.min_humidity,
+            sensor_ranges.max_humidity
+        );
+        Ok(())
+    }
+
+    /// Set how stale delegated machine state may get before anyone can
+    /// checkpoint it with `commit`, 0 to only allow owners to commit
+    pub fn set_max_commit_interval(ctx: Context<UpdateRollupConfig>, max_commit_interval_secs: i64) -> Result<()> {
+        require!(max_commit_interval_secs >= 0, ErrorCode::InvalidCommitInterval);
+
+        let rollup_config = &mut ctx.accounts.rollup_config;
+        rollup_config.max_commit_interval_secs = max_commit_interval_secs;
+
+        msg!("Max commit interval set to: {}s", max_commit_interval_secs);
+        Ok(())
+    }
+
+    pub fn withdraw_treasury(ctx: Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        // Keep the treasury rent exempt
+        let rent_floor = Rent::get()?.minimum_balance(Treasury::SPACE);
+        let available = ctx.accounts.treasury.get_lamports().saturating_sub(rent_floor);
+        require!(amount <= available, ErrorCode::InsufficientTreasuryFunds);
+
+        ctx.accounts.treasury.sub_lamports(amount)?;
+        ctx.accounts.authority.add_lamports(amount)?;
+
+        msg!("Withdrew {} lamports from treasury", amount);
+        Ok(())
+    }
+
+    /// Delegate the cluster to the ephemeral rollup. Only the cluster
+    /// authority can delegate it.
+    pub fn delegate(
+        ctx: Context<DelegateInput>,
+        validator: Option<Pubkey>,
+        commit_frequency_ms: u32,
+    ) -> Result<()> {
+        let record = DelegationRecord::new(
+            validator,
+            commit_frequency_ms,
+            ctx.accounts.rollup_config.max_commit_interval_secs,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // Persist the settings before the account changes owner
+        ctx.accounts.cluster.delegation = record;
+        ctx.accounts.cluster.exit(&crate::ID)?;
+
+        ctx.accounts.delegate_cluster(
             &ctx.accounts.payer,
             &[b"cluster"],
-            DelegateConfig::default(),
+            record.config(),
+        )?;
+        Ok(())
+    }
+
+    /// Delegate a machine to the ephemeral rollup. Its data pages, data
+    /// accounts and plants must be delegated first, because those
+    /// instructions read the machine to check ownership and count them in
+    /// `Machine.delegated_accounts`.
+    pub fn delegate_machine(
+        ctx: Context<DelegateMachine>,
+        validator: Option<Pubkey>,
+        commit_frequency_ms: u32,
+    ) -> Result<()> {
+        let record = DelegationRecord::new(
+            validator,
+            commit_frequency_ms,
+            ctx.accounts.rollup_config.max_commit_interval_secs,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        ctx.accounts.machine.delegation = record;
+        ctx.accounts.machine.exit(&crate::ID)?;
+
+        ctx.accounts.delegate_machine(
+            &ctx.accounts.payer,
+            &[b"machine", ctx.accounts.machine.machine_id.as_bytes()],
+            record.config(),
+        )?;
+        Ok(())
+    }
+
+    /// Delegate a plant of the payer's machine to the ephemeral rollup
+    pub fn delegate_plant(
+        ctx: Context<DelegatePlant>,
+        validator: Option<Pubkey>,
+        commit_frequency_ms: u32,
+    ) -> Result<()> {
+        let record = DelegationRecord::new(
+            validator,
+            commit_frequency_ms,
+            ctx.accounts.rollup_config.max_commit_interval_secs,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        ctx.accounts.plant.delegation = record;
+        ctx.accounts.plant.exit(&crate::ID)?;
+        ctx.accounts.machine.delegated_accounts += 1;
+
+        ctx.accounts.delegate_plant(
+            &ctx.accounts.payer,
+            &[
+                b"plant",
+                ctx.accounts.machine.key().as_ref(),
+                ctx.accounts.plant.plant_name.as_bytes(),
+            ],
+            record.config(),
+        )?;
+        Ok(())
+    }
+
+    /// Delegate the data header of a machine/plant pair to the ephemeral rollup
+    pub fn delegate_data(
+        ctx: Context<DelegateData>,
+        validator: Option<Pubkey>,
+        commit_frequency_ms: u32,
+    ) -> Result<()> {
+        let record = DelegationRecord::new(
+            validator,
+            commit_frequency_ms,
+            ctx.accounts.rollup_config.max_commit_interval_secs,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        ctx.accounts.data.delegation = record;
+        ctx.accounts.data.exit(&crate::ID)?;
+        ctx.accounts.machine.delegated_accounts += 1;
+
+        ctx.accounts.delegate_data(
+            &ctx.accounts.payer,
+            &[
+                b"data",
+                ctx.accounts.machine.machine_id.as_bytes(),
+                ctx.accounts.plant.plant_name.as_bytes(),
+            ],
+            record.config(),
+        )?;
+        Ok(())
+    }
+
+    /// Delegate a data page to the ephemeral rollup. New pages cannot be
+    /// created inside the rollup, so create the season's pages up front with
+    /// `create_data_page` and delegate each of them before the data header.
+    /// The header itself may stay on the base layer; `undelegate_machine`
+    /// then takes it as a read-only account.
+    pub fn delegate_data_page(
+        ctx: Context<DelegateDataPage>,
+        validator: Option<Pubkey>,
+        commit_frequency_ms: u32,
+    ) -> Result<()> {
+        let record = DelegationRecord::new(
+            validator,
+            commit_frequency_ms,
+            ctx.accounts.rollup_config.max_commit_interval_secs,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        ctx.accounts.page.delegation = record;
+        ctx.accounts.page.exit(&crate::ID)?;
+        ctx.accounts.machine.delegated_accounts += 1;
+
+        ctx.accounts.delegate_page(
+            &ctx.accounts.payer,
+            &[
+                b"data_page",
+                ctx.accounts.data.key().as_ref(),
+                &ctx.accounts.page.page_number.to_le_bytes(),
+            ],
+            record.config(),
+        )?;
+        Ok(())
+    }
+
+    /// Checkpoint a delegated machine, plant and its current data page to the
+    /// base layer without undelegating. The owner can commit at any time;
+    /// anyone else once the last commit is older than the rollup config's
+    /// maximum commit interval.
+    pub fn commit(ctx: Context<CommitMachine>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let max_interval = ctx.accounts.rollup_config.max_commit_interval_secs;
+        let machine = &mut ctx.accounts.machine;
+
+        let overdue = max_interval > 0 && now - machine.last_commit_timestamp >= max_interval;
+        require!(
+            ctx.accounts.payer.key() == machine.owner || overdue,
+            ErrorCode::CommitNotDue
+        );
+
+        // Serialize before committing so the checkpoint includes the timestamp
+        machine.last_commit_timestamp = now;
+        machine.exit(&crate::ID)?;
+
+        commit_accounts(
+            &ctx.accounts.payer,
+            vec![
+                &ctx.accounts.machine.to_account_info(),
+                &ctx.accounts.plant.to_account_info(),
+                &ctx.accounts.data.to_account_info(),
+                &ctx.accounts.page.to_account_info(),
+            ],
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
         )?;
+
+        msg!("Machine {} committed at: {}", ctx.accounts.machine.machine_id, now);
         Ok(())
     }
 
-    /// Undelegate the account from the delegation program
+    /// Commit and undelegate the cluster. Only the cluster authority can
+    /// undelegate it.
     pub fn undelegate(ctx: Context<UndelegateAccounts>) -> Result<()> {
         commit_and_undelegate_accounts(
             &ctx.accounts.payer,
-            vec![&ctx.accounts.pda.to_account_info()],
+            vec![&ctx.accounts.cluster.to_account_info()],
             &ctx.accounts.magic_context,
             &ctx.accounts.magic_program,
         )?;
         Ok(())
     }
 
-    pub fn register_machine(ctx: Context<RegisterMachine>, machine_id: String) -> Result<()> {
-        // Validate machine ID isn't already used
-        let machines = &ctx.accounts.cluster.machines;
-        require!(!machines.iter().any(|(id, _)| id == &machine_id), ErrorCode::MachineIdAlreadyExists);
+    /// Commit and undelegate a machine along with every plant, data header
+    /// and data page delegated under it. Those are passed as the first
+    /// `Machine.delegated_accounts` remaining accounts, so none are left
+    /// behind under the delegation program. Data headers of delegated pages
+    /// that were not delegated themselves follow them and are only read to
+    /// tie the pages to the machine. Only the machine owner can undelegate.
+    pub fn undelegate_machine<'info>(
+        ctx: Context<'_, '_, 'info, 'info, UndelegateMachine<'info>>,
+    ) -> Result<()> {
+        let machine_key = ctx.accounts.machine.key();
+        let remaining = ctx.remaining_accounts;
+        let delegated = ctx.accounts.machine.delegated_accounts as usize;
+        require!(remaining.len() >= delegated, ErrorCode::DelegatedAccountsMissing);
+        for (i, info) in remaining.iter().enumerate() {
+            require!(
+                remaining[..i].iter().all(|other| other.key != info.key),
+                ErrorCode::DuplicateAccount
+            );
+        }
+        let (children, headers) = remaining.split_at(delegated);
+
+        let mut data_keys = Vec::new();
+        let mut page_data_keys = Vec::new();
+        for info in children {
+            if let Ok(plant) = Account::<PlantData>::try_from(info) {
+                require!(plant.machine == machine_key, ErrorCode::PlantNotLinkedToMachine);
+            } else if let Ok(data) = Account::<IoTData>::try_from(info) {
+                require!(data.machine == machine_key, ErrorCode::DataNotLinkedToMachine);
+                data_keys.push(info.key());
+            } else {
+                let page = Account::<IoTDataPage>::try_from(info)?;
+                page_data_keys.push(page.data);
+            }
+        }
+        for info in headers {
+            let data = Account::<IoTData>::try_from(info)?;
+            require!(data.machine == machine_key, ErrorCode::DataNotLinkedToMachine);
+            data_keys.push(info.key());
+        }
+
+        // A page is only tied to the machine through its data header
+        require!(
+            page_data_keys.iter().all(|data| data_keys.contains(data)),
+            ErrorCode::DataNotLinkedToMachine
+        );
+
+        ctx.accounts.machine.delegated_accounts = 0;
+        ctx.accounts.machine.exit(&crate::ID)?;
+
+        let mut accounts = vec![ctx.accounts.machine.to_account_info()];
+        accounts.extend(children.iter().cloned());
+        commit_and_undelegate_accounts(
+            &ctx.accounts.payer,
+            accounts.iter().collect(),
+            &ctx.accounts.magic_context,
+            &ctx.accounts.magic_program,
+        )?;
 
-        // Create and initialize the machine account
+        msg!("Machine {} undelegated", ctx.accounts.machine.machine_id);
+        Ok(())
+    }
+
+    pub fn register_machine(ctx: Context<RegisterMachine>, machine_id: String) -> Result<()> {
+        // Create and initialize the machine account. Its PDA is seeded by the
+        // machine ID, so a duplicate ID fails at account creation.
         let machine = &mut ctx.accounts.machine;
         machine.owner = ctx.accounts.user.key();
+        // The owner can upload until a dedicated device key is set
+        machine.device_key = ctx.accounts.user.key();
         machine.machine_id = machine_id.clone();
         machine.is_active = false;
         machine.data_count = 0;
@@ -62,13 +448,26 @@ pub mod agrox_contract {
         machine.last_data_timestamp = 0;
         machine.last_image_timestamp = 0;
         machine.data_used_count = 0;
+        machine.data_price = 0;
+        machine.last_nonce = 0;
+        machine.reward_epoch = 0;
+        machine.epoch_rewards = 0;
+        machine.last_commit_timestamp = 0;
+        machine.delegation = DelegationRecord::default();
+        machine.delegated_accounts = 0;
         machine.plants = Vec::new();
         machine.plant_count = 0;
         machine.bump = ctx.bumps.machine;
 
-        // Add machine to cluster
+        // Add machine to the cluster registry
         let cluster = &mut ctx.accounts.cluster;
-        cluster.machines.push((machine_id.clone(), ctx.accounts.machine.key()));
+        let entry = &mut ctx.accounts.registry_entry;
+        entry.kind = EntityKind::Machine;
+        entry.index = cluster.machine_count;
+        entry.owner = machine.owner;
+        entry.account = machine.key();
+        entry.name = machine_id.clone();
+        entry.bump = ctx.bumps.registry_entry;
         cluster.machine_count += 1;
 
         msg!("Machine registered: {}", machine_id);
@@ -84,12 +483,20 @@ pub mod agrox_contract {
         plant.image_count = 0;
         plant.creation_timestamp = Clock::get()?.unix_timestamp;
         plant.last_update_timestamp = 0;
+        plant.last_measured_at = 0;
         plant.machine = ctx.accounts.machine.key();
+        plant.delegation = DelegationRecord::default();
         plant.bump = ctx.bumps.plant;
 
-        // Add plant to cluster
+        // Add plant to the cluster registry
         let cluster = &mut ctx.accounts.cluster;
-        cluster.plants.push((plant_name.clone(), ctx.accounts.plant.key()));
+        let entry = &mut ctx.accounts.registry_entry;
+        entry.kind = EntityKind::Plant;
+        entry.index = cluster.plant_count;
+        entry.owner = plant.creator;
+        entry.account = plant.key();
+        entry.name = plant_name.clone();
+        entry.bump = ctx.bumps.registry_entry;
         cluster.plant_count += 1;
 
         // Add plant to the machine
@@ -101,6 +508,150 @@ pub mod agrox_contract {
         Ok(())
     }
 
+    /// Rewrite a machine registered before device keys and the fields that
+    /// followed them into the current layout, growing the account to fit.
+    /// The owner stays the device key until `set_device_key` is called. Run
+    /// this before migrating the machine's plants.
+    pub fn migrate_machine(ctx: Context<MigrateMachine>) -> Result<()> {
+        let legacy = &ctx.accounts.machine;
+        let machine = Machine {
+            owner: legacy.owner,
+            device_key: legacy.owner,
+            machine_id: legacy.machine_id.clone(),
+            is_active: legacy.is_active,
+            data_count: legacy.data_count,
+            image_count: legacy.image_count,
+            rewards_earned: legacy.rewards_earned,
+            last_data_timestamp: legacy.last_data_timestamp,
+            last_image_timestamp: legacy.last_image_timestamp,
+            data_used_count: legacy.data_used_count,
+            data_price: 0,
+            last_nonce: 0,
+            reward_epoch: 0,
+            epoch_rewards: 0,
+            last_commit_timestamp: 0,
+            delegation: DelegationRecord::default(),
+            delegated_accounts: 0,
+            plants: legacy.plants.clone(),
+            plant_count: legacy.plant_count,
+            bump: legacy.bump,
+        };
+
+        // `LegacyMachine` is never written back, so store the new layout
+        // directly in the reallocated account
+        let info = legacy.to_account_info();
+        machine.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        msg!("Machine migrated: {}", machine.machine_id);
+        Ok(())
+    }
+
+    /// Move a plant from the legacy `[b"plant", plant_name]` address to its
+    /// machine-namespaced address and close the legacy account. Its machine
+    /// must already have been migrated with `migrate_machine`.
+    pub fn migrate_plant(ctx: Context<MigratePlant>) -> Result<()> {
+        let legacy_plant = &ctx.accounts.legacy_plant;
+        let plant = &mut ctx.accounts.plant;
+        plant.creator = legacy_plant.creator;
+        plant.plant_name = legacy_plant.plant_name.clone();
+        plant.data_count = legacy_plant.data_count;
+        plant.image_count = legacy_plant.image_count;
+        plant.creation_timestamp = legacy_plant.creation_timestamp;
+        plant.last_update_timestamp = legacy_plant.last_update_timestamp;
+        plant.last_measured_at = 0;
+        plant.machine = legacy_plant.machine;
+        plant.delegation = DelegationRecord::default();
+        plant.bump = ctx.bumps.plant;
+
+        // Point the machine's plant list at the new address
+        let legacy_key = legacy_plant.key();
+        let machine = &mut ctx.accounts.machine;
+        if let Some(entry) = machine.plants.iter_mut().find(|(_, key)| *key == legacy_key) {
+            entry.1 = plant.key();
+        }
+
+        // Existing readings follow the plant to its new address. The data
+        // header keeps its address but is rewritten in the current layout,
+        // with its readings moved to the first data page.
+        require!(
+            legacy_plant.data_count == 0 || ctx.accounts.data.is_some(),
+            ErrorCode::PlantDataMissing
+        );
+        if let Some(legacy_data) = &ctx.accounts.data {
+            let (Some(page), Some(page_bump)) = (ctx.accounts.page.as_mut(), ctx.bumps.page) else {
+                return Err(error!(ErrorCode::PlantDataMissing));
+            };
+            require!(
+                legacy_data.data_entries.len() <= IoTDataPage::ENTRIES_PER_PAGE,
+                ErrorCode::BatchExceedsPage
+            );
+
+            let ranges = ctx.accounts.cluster.sensor_ranges;
+            page.data = legacy_data.key();
+            page.page_number = 0;
+            page.data_entries = legacy_data
+                .data_entries
+                .iter()
+                .map(|entry| entry.migrate(&ranges))
+                .collect();
+            page.delegation = DelegationRecord::default();
+            page.bump = page_bump;
+
+            let data = IoTData {
+                machine: legacy_data.machine,
+                plant: plant.key(),
+                total_entries: page.data_entries.len() as u64,
+                delegation: DelegationRecord::default(),
+                bump: legacy_data.bump,
+            };
+            let info = legacy_data.to_account_info();
+            data.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+            plant.last_measured_at = page
+                .data_entries
+                .iter()
+                .map(DataEntry::reading_time)
+                .max()
+                .unwrap_or_default();
+        }
+
+        // Plants created after the cluster registry was introduced have an entry
+        if let Some(registry_entry) = &mut ctx.accounts.registry_entry {
+            registry_entry.account = plant.key();
+        }
+
+        msg!("Plant migrated: {} for machine: {}", plant.plant_name, machine.machine_id);
+        Ok(())
+    }
+
+    /// Create a data page ahead of time. Pages cannot be created inside the
+    /// ephemeral rollup, so an owner planning to run a plant on the rollup
+    /// creates and delegates the pages it will fill before delegating the
+    /// data header, plant and machine. Several pages can be created in one
+    /// transaction.
+    pub fn create_data_page(ctx: Context<CreateDataPage>, page_number: u64) -> Result<()> {
+        let accounts = &mut ctx.accounts;
+        init_data_header(&accounts.machine, &accounts.plant, &mut accounts.data, ctx.bumps.data);
+
+        // Full pages are never written again
+        require!(page_number >= accounts.data.current_page(), ErrorCode::InvalidPageNumber);
+
+        let page = &mut accounts.page;
+        page.data = accounts.data.key();
+        page.page_number = page_number;
+        page.data_entries = Vec::new();
+        page.delegation = DelegationRecord::default();
+        page.bump = ctx.bumps.page;
+
+        msg!(
+            "Data page {} created for machine: {} plant: {}",
+            page_number,
+            accounts.machine.machine_id,
+            accounts.plant.plant_name
+        );
+        Ok(())
+    }
+
     pub fn start_machine(ctx: Context<ControlMachine>) -> Result<()> {
         let machine = &mut ctx.accounts.machine;
         
@@ -127,87 +678,374 @@ pub mod agrox_contract {
         Ok(())
     }
 
+    pub fn set_data_price(ctx: Context<ControlMachine>, data_price: u64) -> Result<()> {
+        let machine = &mut ctx.accounts.machine;
+
+        // Only the machine owner can price its data
+        require!(machine.owner == ctx.accounts.user.key(), ErrorCode::Unauthorized);
+
+        machine.data_price = data_price;
+
+        msg!("Data price for machine {} set to: {} lamports", machine.machine_id, data_price);
+        Ok(())
+    }
+
+    /// Set the hardware key allowed to sign uploads for this machine
+    pub fn set_device_key(ctx: Context<ControlMachine>, device_key: Pubkey) -> Result<()> {
+        let machine = &mut ctx.accounts.machine;
+
+        // Only the machine owner can change the device key
+        require!(machine.owner == ctx.accounts.user.key(), ErrorCode::Unauthorized);
+
+        machine.device_key = device_key;
+
+        msg!("Device key for machine {} set to: {}", machine.machine_id, device_key);
+        Ok(())
+    }
+
+    /// Let `session_key` sign the instructions in `scope` for this machine
+    /// until the session expires. Create sessions before delegating the
+    /// machine, since this reads it on the base layer.
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        duration_secs: i64,
+        scope: u8,
+    ) -> Result<()> {
+        require!(
+            duration_secs > 0 && duration_secs <= SessionToken::MAX_DURATION_SECS,
+            ErrorCode::InvalidSessionDuration
+        );
+        require!(
+            scope != 0 && scope & !SessionToken::ALL_SCOPES == 0,
+            ErrorCode::InvalidSessionScope
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let session = &mut ctx.accounts.session;
+        session.machine = ctx.accounts.machine.key();
+        session.owner = ctx.accounts.owner.key();
+        session.session_key = session_key;
+        session.created_at = now;
+        session.expires_at = now + duration_secs;
+        session.scope = scope;
+        session.bump = ctx.bumps.session;
+
+        msg!(
+            "Session {} created for machine {} until: {}",
+            session_key,
+            ctx.accounts.machine.machine_id,
+            session.expires_at
+        );
+        Ok(())
+    }
+
+    /// Revoke a session and return its rent to the owner
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        msg!("Session {} revoked", ctx.accounts.session.session_key);
+        Ok(())
+    }
+
     pub fn upload_data(
         ctx: Context<UploadData>,
-        temperature: f64,
-        humidity: f64,
-        image_url: Option<String>,
+        temperature: i16,
+        humidity: u16,
+        image: Option<ImageRef>,
+        nonce: u64,
+        metrics: Vec<u8>,
+        measured_at: Option<i64>,
     ) -> Result<()> {
-        let machine = &mut ctx.accounts.machine;
-        let cluster = &mut ctx.accounts.cluster;
-        let plant = &mut ctx.accounts.plant;
-        let data = &mut ctx.accounts.data;
         let clock = Clock::get()?;
-        
-        // Verify the plant belongs to the machine
-        require!(
-            plant.machine == machine.key(),
-            ErrorCode::PlantNotLinkedToMachine
-        );
-        
-        // Initialize data account properties if it's a new account
-        if data.data_entries.is_empty() && data.machine.eq(&Pubkey::default()) {
-            data.machine = machine.key();
-            data.plant = plant.key();
-            data.data_entries = Vec::new();
+        let accounts = &mut ctx.accounts;
+
+        check_uploader(
+            &accounts.machine,
+            &accounts.device.key(),
+            accounts.session.as_deref(),
+            clock.unix_timestamp,
+        )?;
+
+        if let Some(measured_at) = measured_at {
+            accounts.cluster.upload_policy.check_measured_at(measured_at, clock.unix_timestamp)?;
         }
-        
+
+        // Each measurement is counted once
+        accounts.machine.consume_nonce(nonce)?;
+
+        // Extra metrics must match the machine's declared schema
+        SensorSchema::check(accounts.schema.as_deref(), &metrics)?;
+
         // Create new data entry
         let new_entry = DataEntry {
             timestamp: clock.unix_timestamp,
             temperature,
             humidity,
-            image_url: image_url.clone(),
+            image,
             used_count: 0,
+            quality: accounts.cluster.sensor_ranges.classify(temperature, humidity)?,
+            metrics,
+            measured_at,
         };
-        
-        // Add the entry to the data vector
-        data.data_entries.push(new_entry);
-        
-        // Update machine, plant and cluster
-        machine.data_count += 1;
-        machine.last_data_timestamp = clock.unix_timestamp;
-        plant.data_count += 1;
-        plant.last_update_timestamp = clock.unix_timestamp;
-        cluster.total_data_uploads += 1;
-        
-        // Check if this upload includes an image
-        if image_url.is_some() {
-            machine.image_count += 1;
-            plant.image_count += 1;
-            machine.last_image_timestamp = clock.unix_timestamp;
-            
-            // Additional reward for including an image
-            machine.rewards_earned += 10; // 10 tokens per image
+
+        init_data_accounts(
+            &accounts.machine,
+            &accounts.plant,
+            &mut accounts.data,
+            &mut accounts.page,
+            ctx.bumps.data,
+            ctx.bumps.page,
+        );
+        append_entries(
+            &accounts.cluster,
+            &mut accounts.machine,
+            &mut accounts.plant,
+            &mut accounts.data,
+            &mut accounts.page,
+            vec![new_entry],
+            &clock,
+        )
+    }
+
+    /// Upload readings buffered by the device while offline in one
+    /// transaction. Readings carry their measurement time, must be in
+    /// chronological order and must fit in the current data page.
+    pub fn upload_data_batch(
+        ctx: Context<UploadData>,
+        readings: Vec<BatchReading>,
+        nonce: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut ctx.accounts;
+
+        check_uploader(
+            &accounts.machine,
+            &accounts.device.key(),
+            accounts.session.as_deref(),
+            clock.unix_timestamp,
+        )?;
+
+        // The batch as a whole is counted once
+        accounts.machine.consume_nonce(nonce)?;
+
+        let mut entries = Vec::with_capacity(readings.len());
+        for reading in readings {
+            accounts.cluster
+                .upload_policy
+                .check_measured_at(reading.measured_at, clock.unix_timestamp)?;
+            SensorSchema::check(accounts.schema.as_deref(), &reading.metrics)?;
+            entries.push(DataEntry {
+                timestamp: clock.unix_timestamp,
+                temperature: reading.temperature,
+                humidity: reading.humidity,
+                image: reading.image,
+                used_count: 0,
+                quality: accounts.cluster
+                    .sensor_ranges
+                    .classify(reading.temperature, reading.humidity)?,
+                metrics: reading.metrics,
+                measured_at: Some(reading.measured_at),
+            });
         }
-        
-        // Base reward for sensor data
-        machine.rewards_earned += 1; // 1 token per data upload
-        
-        msg!("Data uploaded from machine: {} for plant: {}", machine.machine_id, plant.plant_name);
+
+        init_data_accounts(
+            &accounts.machine,
+            &accounts.plant,
+            &mut accounts.data,
+            &mut accounts.page,
+            ctx.bumps.data,
+            ctx.bumps.page,
+        );
+        append_entries(
+            &accounts.cluster,
+            &mut accounts.machine,
+            &mut accounts.plant,
+            &mut accounts.data,
+            &mut accounts.page,
+            entries,
+            &clock,
+        )
+    }
+
+    /// Upload a reading signed by the machine's device key and submitted by
+    /// any relayer. The transaction must carry an Ed25519 program instruction
+    /// right before this one that verifies the device signature over the
+    /// borsh-serialized `payload`.
+    pub fn upload_signed_data(
+        ctx: Context<UploadSignedData>,
+        payload: SensorPayload,
+        image: Option<ImageRef>,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let accounts = &mut ctx.accounts;
+
+        // The payload must describe this machine and plant
+        require!(
+            payload.machine_id == accounts.machine.machine_id && payload.plant == accounts.plant.key(),
+            ErrorCode::SignedPayloadMismatch
+        );
+
+        // The device attests to the content of the image it references
+        let image_hash = image
+            .as_ref()
+            .map(|image| image.content_hash)
+            .unwrap_or_default();
+        require!(payload.image_hash == image_hash, ErrorCode::SignedPayloadMismatch);
+
+        // Verify the device signature through the preceding Ed25519 instruction
+        let mut message = Vec::new();
+        payload
+            .serialize(&mut message)
+            .map_err(|_| error!(ErrorCode::SignedPayloadMismatch))?;
+        let instructions = accounts.instructions.to_account_info();
+        let current_index = load_current_index_checked(&instructions)? as usize;
+        require!(current_index > 0, ErrorCode::MissingSignatureInstruction);
+        let signature_ix = load_instruction_at_checked(current_index - 1, &instructions)?;
+        verify_ed25519_instruction(&signature_ix, &accounts.machine.device_key, &message)?;
+
+        // Reject replays of an already submitted payload
+        accounts.machine.consume_nonce(payload.nonce)?;
+
+        // The signed timestamp is the device's measurement time
+        accounts.cluster.upload_policy.check_measured_at(payload.timestamp, clock.unix_timestamp)?;
+
+        // Extra metrics must match the machine's declared schema
+        SensorSchema::check(accounts.schema.as_deref(), &payload.metrics)?;
+
+        let new_entry = DataEntry {
+            timestamp: clock.unix_timestamp,
+            temperature: payload.temperature,
+            humidity: payload.humidity,
+            image,
+            used_count: 0,
+            quality: accounts.cluster
+                .sensor_ranges
+                .classify(payload.temperature, payload.humidity)?,
+            metrics: payload.metrics,
+            measured_at: Some(payload.timestamp),
+        };
+
+        init_data_accounts(
+            &accounts.machine,
+            &accounts.plant,
+            &mut accounts.data,
+            &mut accounts.page,
+            ctx.bumps.data,
+            ctx.bumps.page,
+        );
+        append_entries(
+            &accounts.cluster,
+            &mut accounts.machine,
+            &mut accounts.plant,
+            &mut accounts.data,
+            &mut accounts.page,
+            vec![new_entry],
+            &clock,
+        )
+    }
+
+    /// Declare the extra metrics this machine reports. Metrics can be added
+    /// later, but a declared metric's unit and scale can never change so that
+    /// previously uploaded readings keep their meaning.
+    pub fn set_sensor_schema(ctx: Context<SetSensorSchema>, metrics: Vec<MetricSpec>) -> Result<()> {
+        require!(metrics.len() <= SensorSchema::MAX_METRICS, ErrorCode::TooManyMetrics);
+        for (i, spec) in metrics.iter().enumerate() {
+            require!(
+                !metrics[..i].iter().any(|other| other.metric_id == spec.metric_id),
+                ErrorCode::DuplicateMetric
+            );
+        }
+
+        let schema = &mut ctx.accounts.schema;
+        require!(
+            schema.metrics.iter().all(|existing| metrics.contains(existing)),
+            ErrorCode::SchemaChangeNotAllowed
+        );
+
+        schema.machine = ctx.accounts.machine.key();
+        schema.metrics = metrics;
+        schema.bump = ctx.bumps.schema;
+
+        msg!(
+            "Sensor schema for machine {} declares {} metrics",
+            ctx.accounts.machine.machine_id,
+            schema.metrics.len()
+        );
         Ok(())
     }
 
     pub fn use_data(ctx: Context<UseData>, entry_index: u64) -> Result<()> {
-        let data = &mut ctx.accounts.data;
+        let data = &ctx.accounts.data;
+        let page = &mut ctx.accounts.page;
         let machine = &mut ctx.accounts.machine;
         let user = &ctx.accounts.user;
         let cluster = &mut ctx.accounts.cluster;
-        let entry_index = entry_index as usize;
+        let receipt = &mut ctx.accounts.receipt;
+        let clock = Clock::get()?;
+        let slot = IoTDataPage::slot_for(entry_index);
         
         // Ensure the entry index is valid
-        require!(entry_index < data.data_entries.len(), ErrorCode::InvalidDataEntryIndex);
+        require!(
+            entry_index < data.total_entries && slot < page.data_entries.len(),
+            ErrorCode::InvalidDataEntryIndex
+        );
+
+        // A receipt with a buyer set means this entry was already purchased
+        require!(receipt.buyer == Pubkey::default(), ErrorCode::EntryAlreadyPurchased);
+
+        // Split the price between the machine owner and the protocol treasury
+        let price = machine.data_price;
+        let protocol_fee = (price as u128 * cluster.protocol_fee_bps as u128
+            / BPS_DENOMINATOR as u128) as u64;
+        let owner_amount = price - protocol_fee;
+
+        if owner_amount > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: user.to_account_info(),
+                        to: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                owner_amount,
+            )?;
+        }
+
+        if protocol_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: user.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    },
+                ),
+                protocol_fee,
+            )?;
+            ctx.accounts.treasury.fees_collected += protocol_fee;
+        }
         
+        // Record the purchase so the buyer's entitlement can be verified off-chain
+        receipt.buyer = user.key();
+        receipt.data = data.key();
+        receipt.entry_index = entry_index;
+        receipt.price_paid = price;
+        receipt.purchased_at = clock.unix_timestamp;
+        receipt.bump = ctx.bumps.receipt;
+
         // Update usage count for the specific entry
-        data.data_entries[entry_index].used_count += 1;
+        page.data_entries[slot].used_count += 1;
         machine.data_used_count += 1;
         cluster.data_request_count += 1;
         
-        // Calculate and apply rewards to machine owner
-        let reward_amount = 2; // 2 tokens per data usage
-        machine.rewards_earned += reward_amount;
+        // Reward the machine owner for paid purchases only. Free or
+        // self-purchases would let anyone farm mintable reward tokens.
+        if price > 0 && user.key() != machine.owner {
+            let reward_amount = 2; // 2 tokens per data usage
+            machine.rewards_earned += reward_amount;
+        }
         
-        msg!("Data entry {} used by: {}", entry_index, user.key());
+        msg!("Data entry {} used by: {} for {} lamports", entry_index, user.key(), price);
         Ok(())
     }
 
@@ -221,11 +1059,25 @@ pub mod agrox_contract {
         let rewards = machine.rewards_earned;
         require!(rewards > 0, ErrorCode::NoRewardsAvailable);
         
-        // Reset rewards in the machine account
+        // Reset rewards in the machine account before minting
         machine.rewards_earned = 0;
-        
-        // In a real implementation, you would transfer tokens here
-        // For now we just log the claim
+
+        // Mint the earned rewards to the owner's associated token account
+        let authority_bump = ctx.accounts.cluster.reward_authority_bump;
+        let signer_seeds: &[&[&[u8]]] = &[&[b"reward_authority", &[authority_bump]]];
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    to: ctx.accounts.user_token_account.to_account_info(),
+                    authority: ctx.accounts.reward_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            rewards,
+        )?;
+
         msg!("Rewards claimed: {} tokens for machine: {}", rewards, machine.machine_id);
         
         Ok(())
@@ -233,6 +1085,215 @@ pub mod agrox_contract {
 }
 
 
+/// Set up the data header of a machine/plant pair the first time it is used
+fn init_data_header(
+    machine: &Account<Machine>,
+    plant: &Account<PlantData>,
+    data: &mut Account<IoTData>,
+    data_bump: u8,
+) {
+    if data.machine.eq(&Pubkey::default()) {
+        data.machine = machine.key();
+        data.plant = plant.key();
+        data.total_entries = 0;
+        data.delegation = DelegationRecord::default();
+        data.bump = data_bump;
+    }
+}
+
+/// Set up the data header and the current page the first time they are used
+fn init_data_accounts(
+    machine: &Account<Machine>,
+    plant: &Account<PlantData>,
+    data: &mut Account<IoTData>,
+    page: &mut Account<IoTDataPage>,
+    data_bump: u8,
+    page_bump: u8,
+) {
+    init_data_header(machine, plant, data, data_bump);
+
+    // Initialize the page if this upload rolled over into a new one
+    if page.data.eq(&Pubkey::default()) {
+        page.data = data.key();
+        page.page_number = data.current_page();
+        page.data_entries = Vec::new();
+        page.delegation = DelegationRecord::default();
+        page.bump = page_bump;
+    }
+}
+
+/// Shared by the upload instructions: append the entries to the current page,
+/// update counters once and accrue rewards according to the cluster's upload
+/// policy. Intervals are measured on the chain clock, never on device-supplied
+/// measurement times: an upload may carry one reading per minimum interval
+/// elapsed since the machine's previous upload, so a buffered batch earns no
+/// faster than live readings would have.
+fn append_entries(
+    cluster: &Cluster,
+    machine: &mut Account<Machine>,
+    plant: &mut Account<PlantData>,
+    data: &mut Account<IoTData>,
+    page: &mut Account<IoTDataPage>,
+    entries: Vec<DataEntry>,
+    clock: &Clock,
+) -> Result<()> {
+    // Verify the plant belongs to the machine
+    require!(
+        plant.machine == machine.key(),
+        ErrorCode::PlantNotLinkedToMachine
+    );
+    require!(!entries.is_empty(), ErrorCode::EmptyBatch);
+    require!(
+        page.data_entries.len() + entries.len() <= IoTDataPage::ENTRIES_PER_PAGE,
+        ErrorCode::BatchExceedsPage
+    );
+
+    let policy = cluster.upload_policy;
+    let now = clock.unix_timestamp;
+    let count = entries.len() as u64;
+    let mut image_count = 0;
+    let mut reward = 0;
+    let mut data_allowance = UploadPolicy::readings_allowed(
+        machine.last_data_timestamp,
+        now,
+        policy.min_upload_interval_secs,
+    );
+    let mut image_allowance = UploadPolicy::readings_allowed(
+        machine.last_image_timestamp,
+        now,
+        policy.min_image_interval_secs,
+    );
+
+    for mut entry in entries {
+        let reading_time = entry.reading_time();
+        let has_image = entry.image.is_some();
+
+        if let Some(image) = &entry.image {
+            image.validate()?;
+        }
+
+        // Device measurement times must be after every earlier reading of the
+        // plant, including readings stamped with the chain clock
+        if let Some(measured_at) = entry.measured_at {
+            require!(measured_at > plant.last_measured_at, ErrorCode::MeasurementNotIncreasing);
+        }
+        plant.last_measured_at = plant.last_measured_at.max(reading_time);
+
+        // Readings beyond the upload's allowance are rejected or earn no reward
+        let data_throttled = data_allowance == 0;
+        let image_throttled = has_image && image_allowance == 0;
+        if policy.throttle_mode == ThrottleMode::Reject {
+            require!(!data_throttled && !image_throttled, ErrorCode::UploadTooFrequent);
+        }
+        data_allowance = data_allowance.saturating_sub(1);
+        if has_image {
+            image_allowance = image_allowance.saturating_sub(1);
+        }
+
+        // Readings accepted beyond the allowance are flagged for buyers
+        if data_throttled && entry.quality == DataQuality::Ok {
+            entry.quality = DataQuality::Suspect;
+        }
+        let quality_ok = entry.quality == DataQuality::Ok;
+
+        // Check if this upload includes an image
+        if has_image {
+            image_count += 1;
+
+            // Additional reward for including an image
+            if quality_ok && !image_throttled {
+                reward += 10; // 10 tokens per image
+            }
+        }
+
+        // Base reward for sensor data, only for readings flagged as ok
+        if quality_ok {
+            reward += 1; // 1 token per data upload
+        }
+
+        // Add the entry to the current page
+        page.data_entries.push(entry);
+    }
+
+    // Update data, machine and plant once for the whole upload. Uploads
+    // leave the cluster untouched so it never has to be delegated.
+    data.total_entries += count;
+    machine.data_count += count;
+    machine.image_count += image_count;
+    plant.data_count += count;
+    plant.image_count += image_count;
+    plant.last_update_timestamp = now;
+    machine.last_data_timestamp = now;
+    if image_count > 0 {
+        machine.last_image_timestamp = now;
+    }
+
+    let credited = machine.accrue_upload_reward(reward, policy.epoch_reward_cap, clock.epoch);
+
+    msg!(
+        "{} readings uploaded from machine: {} for plant: {} ({} reward tokens)",
+        count,
+        machine.machine_id,
+        plant.plant_name,
+        credited
+    );
+    Ok(())
+}
+
+/// Uploads must be signed by the machine's device key or by the key of a live
+/// session with upload scope
+fn check_uploader(
+    machine: &Machine,
+    device: &Pubkey,
+    session: Option<&SessionToken>,
+    now: i64,
+) -> Result<()> {
+    if *device == machine.device_key {
+        return Ok(());
+    }
+
+    let session = session.ok_or(ErrorCode::InvalidDeviceKey)?;
+    session.check(SessionToken::SCOPE_UPLOAD, now)
+}
+
+/// Check that `ix` is an Ed25519 program instruction verifying a single
+/// signature by `signer` over exactly `message`, with all data inline.
+fn verify_ed25519_instruction(ix: &Instruction, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    require_keys_eq!(ix.program_id, ed25519_program::ID, ErrorCode::MissingSignatureInstruction);
+
+    // Layout: num_signatures (u8), padding (u8), then one offsets struct of
+    // seven little-endian u16 values
+    let data = &ix.data;
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::MissingSignatureInstruction);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_ix_index = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_ix_index = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_ix_index = read_u16(14);
+
+    // u16::MAX means the data lives in the Ed25519 instruction itself
+    require!(
+        signature_ix_index == u16::MAX
+            && public_key_ix_index == u16::MAX
+            && message_ix_index == u16::MAX,
+        ErrorCode::MissingSignatureInstruction
+    );
+
+    let public_key = data
+        .get(public_key_offset..public_key_offset + 32)
+        .ok_or(error!(ErrorCode::MissingSignatureInstruction))?;
+    require!(public_key == signer.as_ref(), ErrorCode::InvalidDeviceKey);
+
+    let signed_message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(error!(ErrorCode::MissingSignatureInstruction))?;
+    require!(signed_message == message, ErrorCode::SignedPayloadMismatch);
+
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
@@ -250,14 +1311,139 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct InitializeRewardMint<'info> {
+    #[account(
+        mut,
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub cluster: Account<'info, Cluster>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"reward_mint"],
+        bump,
+        mint::decimals = REWARD_DECIMALS,
+        mint::authority = reward_authority,
+    )]
+    pub reward_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA that only acts as the mint authority of `reward_mint`
+    #[account(seeds = [b"reward_authority"], bump)]
+    pub reward_authority: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub cluster: Account<'info, Cluster>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = Treasury::SPACE,
+        seeds = [b"treasury"],
+        bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeRollupConfig<'info> {
+    #[account(
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub cluster: Account<'info, Cluster>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = RollupConfig::SPACE,
+        seeds = [b"rollup_config"],
+        bump
+    )]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRollupConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"rollup_config"],
+        bump = rollup_config.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCluster<'info> {
+    #[account(
+        mut,
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub cluster: Account<'info, Cluster>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        has_one = authority @ ErrorCode::Unauthorized,
+    )]
+    pub cluster: Account<'info, Cluster>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(machine_id: String)]
 pub struct RegisterMachine<'info> {
     #[account(
         mut,
-        realloc = Cluster::BASE_SPACE + (Cluster::MACHINE_ENTRY_SIZE * (cluster.machines.len() + 1)),
-        realloc::payer = user,
-        realloc::zero = false,
+        seeds = [b"cluster"],
+        bump = cluster.bump
     )]
     pub cluster: Account<'info, Cluster>,
     
@@ -265,10 +1451,19 @@ pub struct RegisterMachine<'info> {
         init,
         payer = user,
         space = Machine::SPACE,
-        seeds = [b"machine", machine_id.as_bytes()],
+        seeds = [b"machine", name_seed(&machine_id, ErrorCode::MachineIdTooLong)?],
         bump
     )]
     pub machine: Account<'info, Machine>,
+
+    #[account(
+        init,
+        payer = user,
+        space = RegistryEntry::SPACE,
+        seeds = [b"machine_entry", &cluster.machine_count.to_le_bytes()],
+        bump
+    )]
+    pub registry_entry: Account<'info, RegistryEntry>,
     
     #[account(mut)]
     pub user: Signer<'info>,
@@ -279,23 +1474,43 @@ pub struct RegisterMachine<'info> {
 #[derive(Accounts)]
 #[instruction(plant_name: String)]
 pub struct CreatePlant<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"cluster"],
+        bump = cluster.bump
+    )]
     pub cluster: Account<'info, Cluster>,
-    
+
     #[account(
         init,
         payer = user,
-        space = PlantData::SPACE,
-        seeds = [b"plant", plant_name.as_bytes()],
+        space = RegistryEntry::SPACE,
+        seeds = [b"plant_entry", &cluster.plant_count.to_le_bytes()],
         bump
     )]
-    pub plant: Account<'info, PlantData>,
+    pub registry_entry: Account<'info, RegistryEntry>,
     
     #[account(
         mut,
-        constraint = machine.owner == user.key() @ ErrorCode::Unauthorized
+        constraint = machine.owner == user.key() @ ErrorCode::Unauthorized,
+        realloc = Machine::space(machine.plants.len() + 1),
+        realloc::payer = user,
+        realloc::zero = false,
     )]
     pub machine: Account<'info, Machine>,
+
+    #[account(
+        init,
+        payer = user,
+        space = PlantData::SPACE,
+        seeds = [
+            b"plant",
+            machine.key().as_ref(),
+            name_seed(&plant_name, ErrorCode::PlantNameTooLong)?,
+        ],
+        bump
+    )]
+    pub plant: Account<'info, PlantData>,
     
     #[account(mut)]
     pub user: Signer<'info>,
@@ -304,101 +1519,739 @@ pub struct CreatePlant<'info> {
 }
 
 #[derive(Accounts)]
-pub struct ControlMachine<'info> {
+pub struct MigrateMachine<'info> {
+    #[account(
+        mut,
+        seeds = [b"machine", machine.machine_id.as_bytes()],
+        bump = machine.bump,
+        constraint = machine.owner == user.key() @ ErrorCode::Unauthorized,
+        realloc = Machine::space(machine.plants.len()),
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub machine: Account<'info, LegacyMachine>,
+
     #[account(mut)]
-    pub machine: Account<'info, Machine>,
-    
     pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UploadData<'info> {
-    #[account(mut)]
-    pub cluster: Account<'info, Cluster>,
-    
+pub struct MigratePlant<'info> {
     #[account(
         mut,
-        constraint = machine.is_active @ ErrorCode::MachineNotActive,
+        constraint = machine.owner == user.key() @ ErrorCode::Unauthorized
     )]
     pub machine: Account<'info, Machine>,
 
     #[account(
         mut,
-        constraint = plant.machine == machine.key() @ ErrorCode::PlantNotLinkedToMachine,
+        seeds = [b"plant", legacy_plant.plant_name.as_bytes()],
+        bump = legacy_plant.bump,
+        constraint = legacy_plant.machine == machine.key() @ ErrorCode::PlantNotLinkedToMachine,
+        close = user
+    )]
+    pub legacy_plant: Account<'info, LegacyPlantData>,
+
+    #[account(
+        init,
+        payer = user,
+        space = PlantData::SPACE,
+        seeds = [b"plant", machine.key().as_ref(), legacy_plant.plant_name.as_bytes()],
+        bump
     )]
     pub plant: Account<'info, PlantData>,
-    
+
+    #[account(seeds = [b"cluster"], bump = cluster.bump)]
+    pub cluster: Account<'info, Cluster>,
+
+    /// Legacy data header of the plant, required if it has any uploads.
+    /// Shrunk to the current header size, which keeps its address.
+    #[account(
+        mut,
+        seeds = [b"data", machine.machine_id.as_bytes(), legacy_plant.plant_name.as_bytes()],
+        bump = data.bump,
+        constraint = data.plant == legacy_plant.key() @ ErrorCode::DataNotLinkedToMachine,
+        realloc = IoTData::SPACE,
+        realloc::payer = user,
+        realloc::zero = false,
+    )]
+    pub data: Option<Account<'info, LegacyIoTData>>,
+
+    /// First data page of the plant, receives the legacy readings. Required
+    /// along with `data`.
+    #[account(
+        init,
+        payer = user,
+        space = IoTDataPage::SPACE,
+        seeds = [b"data_page", data.key().as_ref(), &0u64.to_le_bytes()],
+        bump
+    )]
+    pub page: Option<Account<'info, IoTDataPage>>,
+
+    /// Registry entry of the plant, if it was registered after the cluster
+    /// registry was introduced
+    #[account(
+        mut,
+        constraint = registry_entry.account == legacy_plant.key() @ ErrorCode::UnregisteredPlant
+    )]
+    pub registry_entry: Option<Account<'info, RegistryEntry>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(page_number: u64)]
+pub struct CreateDataPage<'info> {
+    #[account(
+        constraint = machine.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        constraint = plant.machine == machine.key() @ ErrorCode::PlantNotLinkedToMachine
+    )]
+    pub plant: Account<'info, PlantData>,
+
     #[account(
         init_if_needed,
-        payer = payer,
-        space = IoTData::space(1),
+        payer = owner,
+        space = IoTData::SPACE,
         seeds = [b"data", machine.machine_id.as_bytes(), plant.plant_name.as_bytes()],
         bump
     )]
     pub data: Account<'info, IoTData>,
-    
+
+    #[account(
+        init,
+        payer = owner,
+        space = IoTDataPage::SPACE,
+        seeds = [b"data_page", data.key().as_ref(), &page_number.to_le_bytes()],
+        bump
+    )]
+    pub page: Account<'info, IoTDataPage>,
+
     #[account(mut)]
-    pub payer: Signer<'info>,
-    
+    pub owner: Signer<'info>,
+
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct UseData<'info> {
-    #[account(mut)]
-    pub cluster: Account<'info, Cluster>,
-    
+pub struct ControlMachine<'info> {
     #[account(mut)]
     pub machine: Account<'info, Machine>,
     
-    #[account(mut)]
-    pub data: Account<'info, IoTData>,
-    
     pub user: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct ClaimRewards<'info> {
-    #[account(mut)]
+#[instruction(session_key: Pubkey)]
+pub struct CreateSession<'info> {
+    #[account(
+        constraint = machine.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = SessionToken::SPACE,
+        seeds = [b"session", machine.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, SessionToken>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        close = owner,
+        has_one = owner @ ErrorCode::Unauthorized
+    )]
+    pub session: Account<'info, SessionToken>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UploadData<'info> {
+    #[account(seeds = [b"cluster"], bump = cluster.bump)]
+    pub cluster: Account<'info, Cluster>,
+    
+    #[account(
+        mut,
+        constraint = machine.is_active @ ErrorCode::MachineNotActive,
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        mut,
+        constraint = plant.machine == machine.key() @ ErrorCode::PlantNotLinkedToMachine,
+    )]
+    pub plant: Account<'info, PlantData>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = IoTData::SPACE,
+        seeds = [b"data", machine.machine_id.as_bytes(), plant.plant_name.as_bytes()],
+        bump
+    )]
+    pub data: Account<'info, IoTData>,
+
+    /// Page holding the next entry. A new page is created automatically
+    /// once the previous one is full.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = IoTDataPage::SPACE,
+        seeds = [b"data_page", data.key().as_ref(), &data.current_page().to_le_bytes()],
+        bump
+    )]
+    pub page: Account<'info, IoTDataPage>,
+
+    /// Required when the upload carries extra metrics
+    #[account(seeds = [b"schema", machine.key().as_ref()], bump = schema.bump)]
+    pub schema: Option<Account<'info, SensorSchema>>,
+
+    /// Machine's device key, or a session key when `session` is passed;
+    /// may be the same account as `payer`
+    pub device: Signer<'info>,
+
+    /// Session letting `device` upload in place of the device key
+    #[account(
+        seeds = [b"session", machine.key().as_ref(), device.key().as_ref()],
+        bump = session.bump
+    )]
+    pub session: Option<Account<'info, SessionToken>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UploadSignedData<'info> {
+    #[account(seeds = [b"cluster"], bump = cluster.bump)]
+    pub cluster: Account<'info, Cluster>,
+
+    #[account(
+        mut,
+        constraint = machine.is_active @ ErrorCode::MachineNotActive,
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        mut,
+        constraint = plant.machine == machine.key() @ ErrorCode::PlantNotLinkedToMachine,
+    )]
+    pub plant: Account<'info, PlantData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = IoTData::SPACE,
+        seeds = [b"data", machine.machine_id.as_bytes(), plant.plant_name.as_bytes()],
+        bump
+    )]
+    pub data: Account<'info, IoTData>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = IoTDataPage::SPACE,
+        seeds = [b"data_page", data.key().as_ref(), &data.current_page().to_le_bytes()],
+        bump
+    )]
+    pub page: Account<'info, IoTDataPage>,
+
+    /// Required when the payload carries extra metrics
+    #[account(seeds = [b"schema", machine.key().as_ref()], bump = schema.bump)]
+    pub schema: Option<Account<'info, SensorSchema>>,
+
+    /// Relayer paying for the transaction
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSensorSchema<'info> {
+    #[account(
+        constraint = machine.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = SensorSchema::SPACE,
+        seeds = [b"schema", machine.key().as_ref()],
+        bump
+    )]
+    pub schema: Account<'info, SensorSchema>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(entry_index: u64)]
+pub struct UseData<'info> {
+    #[account(mut)]
+    pub cluster: Account<'info, Cluster>,
+    
+    #[account(mut)]
     pub machine: Account<'info, Machine>,
     
+    #[account(
+        constraint = data.machine == machine.key() @ ErrorCode::DataNotLinkedToMachine,
+    )]
+    pub data: Account<'info, IoTData>,
+
+    #[account(
+        mut,
+        seeds = [b"data_page", data.key().as_ref(), &IoTData::page_for(entry_index).to_le_bytes()],
+        bump = page.bump
+    )]
+    pub page: Account<'info, IoTDataPage>,
+
+    /// Machine owner receiving the payment
+    #[account(mut, address = machine.owner @ ErrorCode::Unauthorized)]
+    pub owner: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury"],
+        bump = treasury.bump
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = AccessReceipt::SPACE,
+        seeds = [b"receipt", data.key().as_ref(), &entry_index.to_le_bytes(), user.key().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, AccessReceipt>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        constraint = cluster.reward_mint != Pubkey::default() @ ErrorCode::RewardMintNotInitialized,
+    )]
+    pub cluster: Account<'info, Cluster>,
+
+    #[account(mut)]
+    pub machine: Account<'info, Machine>,
+
+    #[account(mut, address = cluster.reward_mint)]
+    pub reward_mint: Account<'info, Mint>,
+
+    /// CHECK: PDA mint authority, validated by its seeds
+    #[account(seeds = [b"reward_authority"], bump = cluster.reward_authority_bump)]
+    pub reward_authority: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = reward_mint,
+        associated_token::authority = user,
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
     pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
 }
 
 #[account]
 pub struct Cluster {
     pub authority: Pubkey,
     pub machine_count: u64,
-    pub total_data_uploads: u64,
     pub data_request_count: u64,
     pub plant_count: u64,
-    pub machines: Vec<(String, Pubkey)>,
-    pub plants: Vec<(String, Pubkey)>,
+    pub reward_mint: Pubkey,
+    pub reward_authority_bump: u8,
+    pub protocol_fee_bps: u16,
+    pub upload_policy: UploadPolicy,
+    pub sensor_ranges: SensorRanges,
+    pub delegation: DelegationRecord,
     pub bump: u8,
 }
 
 impl Cluster {
-    pub const SPACE: usize = Self::BASE_SPACE; // Initially no machines or plants
-    
-    pub const BASE_SPACE: usize = 8 + // discriminator
+    pub const SPACE: usize = 8 + // discriminator
                             32 + // authority
                             8 + // machine_count
-                            8 + // total_data_uploads
                             8 + // data_request_count
                             8 + // plant_count
-                            4 + // machines vec length
-                            4 + // plants vec length
+                            32 + // reward_mint
+                            1 + // reward_authority_bump
+                            2 + // protocol_fee_bps
+                            UploadPolicy::SPACE + // upload_policy
+                            SensorRanges::SPACE + // sensor_ranges
+                            DelegationRecord::SPACE + // delegation
+                            1; // bump
+}
+
+/// Ephemeral rollup settings, kept out of the cluster so they stay readable on
+/// the base layer while the cluster is delegated. Derived from
+/// `[b"rollup_config"]`.
+#[account]
+pub struct RollupConfig {
+    pub authority: Pubkey,
+    /// How stale delegated machine state may get before anyone can commit
+    /// it, 0 to only allow owners to commit. Also caps delegation commit
+    /// frequencies.
+    pub max_commit_interval_secs: i64,
+    pub bump: u8,
+}
+
+impl RollupConfig {
+    pub const SPACE: usize = 8 + // discriminator
+                            32 + // authority
+                            8 + // max_commit_interval_secs
+                            1; // bump
+}
+
+/// What happens to uploads that arrive inside the minimum interval
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ThrottleMode {
+    #[default]
+    Reject,
+    NoReward,
+}
+
+/// Cluster-wide limits on how often machines can earn upload rewards
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct UploadPolicy {
+    pub min_upload_interval_secs: i64,
+    pub min_image_interval_secs: i64,
+    /// Maximum upload rewards per machine per epoch, 0 for no cap
+    pub epoch_reward_cap: u64,
+    pub throttle_mode: ThrottleMode,
+    /// How far ahead of the chain clock a device timestamp may be
+    pub max_clock_skew_secs: i64,
+    /// How old a backfilled reading may be, 0 for no limit
+    pub max_backfill_secs: i64,
+}
+
+impl Default for UploadPolicy {
+    fn default() -> Self {
+        Self {
+            min_upload_interval_secs: 0,
+            min_image_interval_secs: 0,
+            epoch_reward_cap: 0,
+            throttle_mode: ThrottleMode::default(),
+            max_clock_skew_secs: Self::DEFAULT_MAX_CLOCK_SKEW_SECS,
+            max_backfill_secs: Self::DEFAULT_MAX_BACKFILL_SECS,
+        }
+    }
+}
+
+impl UploadPolicy {
+    pub const SPACE: usize = 8 + // min_upload_interval_secs
+                            8 + // min_image_interval_secs
+                            8 + // epoch_reward_cap
+                            1 + // throttle_mode
+                            8 + // max_clock_skew_secs
+                            8; // max_backfill_secs
+
+    /// Same windows as `contract_7` applies to new sensor data accounts
+    pub const DEFAULT_MAX_CLOCK_SKEW_SECS: i64 = 60;
+    pub const DEFAULT_MAX_BACKFILL_SECS: i64 = 7 * 24 * 60 * 60;
+
+    /// How many readings an upload landing at `now` may carry within
+    /// `interval`, given that the previous upload landed at `last`: one per
+    /// interval elapsed since, or one for a machine's first upload
+    pub fn readings_allowed(last: i64, now: i64, interval: i64) -> u64 {
+        if interval <= 0 {
+            u64::MAX
+        } else if last == 0 {
+            1
+        } else {
+            (now.saturating_sub(last).max(0) / interval) as u64
+        }
+    }
+
+    /// Check a device-supplied measurement time against the chain clock
+    pub fn check_measured_at(&self, measured_at: i64, now: i64) -> Result<()> {
+        require!(
+            measured_at <= now.saturating_add(self.max_clock_skew_secs),
+            ErrorCode::MeasurementInFuture
+        );
+        require!(
+            self.max_backfill_secs == 0 || measured_at >= now.saturating_sub(self.max_backfill_secs),
+            ErrorCode::MeasurementTooOld
+        );
+        Ok(())
+    }
+}
+
+/// Plausible sensor values; readings outside are stored as out of range
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct SensorRanges {
+    pub min_temperature: i16,
+    pub max_temperature: i16,
+    pub min_humidity: u16,
+    pub max_humidity: u16,
+}
+
+impl Default for SensorRanges {
+    fn default() -> Self {
+        Self {
+            min_temperature: -4_000, // -40 °C
+            max_temperature: 8_500, // 85 °C
+            min_humidity: 0,
+            max_humidity: MAX_HUMIDITY_PERMILLE,
+        }
+    }
+}
+
+impl SensorRanges {
+    pub const SPACE: usize = 2 + // min_temperature
+                            2 + // max_temperature
+                            2 + // min_humidity
+                            2; // max_humidity
+
+    pub fn is_valid(&self) -> bool {
+        self.min_temperature < self.max_temperature
+            && self.min_humidity < self.max_humidity
+            && self.max_humidity <= MAX_HUMIDITY_PERMILLE
+    }
+
+    /// Reject physically impossible values outright and flag implausible ones
+    pub fn classify(&self, temperature: i16, humidity: u16) -> Result<DataQuality> {
+        require!(humidity <= MAX_HUMIDITY_PERMILLE, ErrorCode::InvalidSensorValue);
+
+        let in_range = (self.min_temperature..=self.max_temperature).contains(&temperature)
+            && (self.min_humidity..=self.max_humidity).contains(&humidity);
+        Ok(if in_range { DataQuality::Ok } else { DataQuality::OutOfRange })
+    }
+}
+
+/// Ephemeral rollup settings an account was last delegated with, kept for
+/// auditing. Stays at its default until the account is first delegated.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct DelegationRecord {
+    /// Validator the account is pinned to, `None` for any validator
+    pub validator: Option<Pubkey>,
+    pub commit_frequency_ms: u32,
+    pub delegated_at: i64,
+}
+
+impl DelegationRecord {
+    pub const SPACE: usize = (1 + 32) + // validator
+                            4 + // commit_frequency_ms
+                            8; // delegated_at
+
+    pub const MIN_COMMIT_FREQUENCY_MS: u32 = 1_000;
+    pub const MAX_COMMIT_FREQUENCY_MS: u32 = 60 * 60 * 1_000;
+
+    /// Check requested delegation settings. The commit frequency may not
+    /// exceed the rollup config's maximum commit interval when one is set.
+    pub fn new(
+        validator: Option<Pubkey>,
+        commit_frequency_ms: u32,
+        max_commit_interval_secs: i64,
+        now: i64,
+    ) -> Result<Self> {
+        require!(
+            validator != Some(Pubkey::default()),
+            ErrorCode::InvalidDelegationValidator
+        );
+
+        let max_frequency_ms = if max_commit_interval_secs > 0 {
+            (max_commit_interval_secs as u64)
+                .saturating_mul(1_000)
+                .min(Self::MAX_COMMIT_FREQUENCY_MS as u64)
+        } else {
+            Self::MAX_COMMIT_FREQUENCY_MS as u64
+        };
+        require!(
+            commit_frequency_ms >= Self::MIN_COMMIT_FREQUENCY_MS
+                && commit_frequency_ms as u64 <= max_frequency_ms,
+            ErrorCode::InvalidCommitFrequency
+        );
+
+        Ok(Self {
+            validator,
+            commit_frequency_ms,
+            delegated_at: now,
+        })
+    }
+
+    pub fn config(&self) -> DelegateConfig {
+        DelegateConfig {
+            commit_frequency_ms: self.commit_frequency_ms,
+            validator: self.validator,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntityKind {
+    Machine,
+    Plant,
+}
+
+/// One registered machine or plant. Entries are derived from
+/// `[b"machine_entry", index (le bytes)]` or `[b"plant_entry", index (le bytes)]`
+/// where `index` runs from 0 to `Cluster.machine_count` / `Cluster.plant_count`.
+///
+/// Clients can also enumerate them with `getProgramAccounts` filters:
+/// - `memcmp` at offset 0: the `RegistryEntry` account discriminator
+/// - `memcmp` at offset 8: `kind` (0 = machine, 1 = plant)
+/// - `memcmp` at offset 17: `owner`, to list a single farmer's entities
+#[account]
+pub struct RegistryEntry {
+    pub kind: EntityKind,
+    pub index: u64,
+    pub owner: Pubkey,
+    pub account: Pubkey,
+    pub name: String,
+    pub bump: u8,
+}
+
+impl RegistryEntry {
+    pub const SPACE: usize = 8 + // discriminator
+                            1 + // kind
+                            8 + // index
+                            32 + // owner
+                            32 + // account
+                            (4 + MAX_NAME_LENGTH) + // name
                             1; // bump
-                            
-    pub const MACHINE_ENTRY_SIZE: usize = 36 + // machine_id (max 32 chars + 4 bytes for length)
-                                        32; // pubkey
-                                        
-    pub const PLANT_ENTRY_SIZE: usize = 36 + // plant_name (max 32 chars + 4 bytes for length)
-                                      32; // pubkey
 }
 
 #[account]
 pub struct Machine {
+    pub owner: Pubkey,
+    pub device_key: Pubkey,
+    pub machine_id: String,
+    pub is_active: bool,
+    pub data_count: u64,
+    pub image_count: u64,
+    pub rewards_earned: u64,
+    pub last_data_timestamp: i64,
+    pub last_image_timestamp: i64,
+    pub data_used_count: u64,
+    pub data_price: u64,
+    pub last_nonce: u64,
+    pub reward_epoch: u64,
+    pub epoch_rewards: u64,
+    pub last_commit_timestamp: i64,
+    pub delegation: DelegationRecord,
+    /// Plants, data headers and data pages currently delegated under this
+    /// machine, all of which `undelegate_machine` must be given
+    pub delegated_accounts: u32,
+    pub plants: Vec<(String, Pubkey)>,
+    pub plant_count: u64,
+    pub bump: u8,
+}
+
+impl Machine {
+    pub const SPACE: usize = Self::BASE_SPACE; // Initially no plants
+
+    pub const BASE_SPACE: usize = 8 + // discriminator
+                            32 + // owner
+                            32 + // device_key
+                            (4 + MAX_NAME_LENGTH) + // machine_id
+                            1 + // is_active
+                            8 + // data_count
+                            8 + // image_count
+                            8 + // rewards_earned
+                            8 + // last_data_timestamp
+                            8 + // last_image_timestamp
+                            8 + // data_used_count
+                            8 + // data_price
+                            8 + // last_nonce
+                            8 + // reward_epoch
+                            8 + // epoch_rewards
+                            8 + // last_commit_timestamp
+                            DelegationRecord::SPACE + // delegation
+                            4 + // delegated_accounts
+                            4 + // plants vec length
+                            8 + // plant_count
+                            1; // bump
+
+    pub const PLANT_ENTRY_SIZE: usize = (4 + MAX_NAME_LENGTH) + // plant_name
+                                      32; // pubkey
+
+    /// Address of the machine registered under `machine_id`
+    pub fn find_address(machine_id: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"machine", machine_id.as_bytes()], &crate::ID)
+    }
+
+    /// Account size once `plant_count` plants are attached
+    pub fn space(plant_count: usize) -> usize {
+        Self::BASE_SPACE + (Self::PLANT_ENTRY_SIZE * plant_count)
+    }
+
+    /// Accept an upload nonce only if it is strictly greater than the last one.
+    /// Nonces start at 1 for a newly registered machine.
+    pub fn consume_nonce(&mut self, nonce: u64) -> Result<()> {
+        require!(nonce > self.last_nonce, ErrorCode::StaleNonce);
+        self.last_nonce = nonce;
+        Ok(())
+    }
+
+    /// Credit upload rewards, capped at `epoch_cap` per epoch (0 = no cap).
+    /// Returns the amount actually credited.
+    pub fn accrue_upload_reward(&mut self, amount: u64, epoch_cap: u64, epoch: u64) -> u64 {
+        if self.reward_epoch != epoch {
+            self.reward_epoch = epoch;
+            self.epoch_rewards = 0;
+        }
+
+        let credited = if epoch_cap == 0 {
+            amount
+        } else {
+            amount.min(epoch_cap.saturating_sub(self.epoch_rewards))
+        };
+        self.epoch_rewards += credited;
+        self.rewards_earned += credited;
+        credited
+    }
+}
+
+/// `Machine` as laid out before device keys were added, read only by
+/// `migrate_machine`. Legacy accounts carry the `Machine` discriminator, so
+/// the account traits are implemented by hand instead of with `#[account]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyMachine {
     pub owner: Pubkey,
     pub machine_id: String,
     pub is_active: bool,
@@ -413,78 +2266,533 @@ pub struct Machine {
     pub bump: u8,
 }
 
-impl Machine {
-    pub const SPACE: usize = 8 + // discriminator
-                            32 + // owner
-                            36 + // machine_id (max 32 chars + 4 bytes for length)
-                            1 + // is_active
-                            8 + // data_count
-                            8 + // image_count
-                            8 + // rewards_earned
-                            8 + // last_data_timestamp
-                            8 + // last_image_timestamp
-                            8 + // data_used_count
-                            4 + // plants vec length
-                            8 + // plant_count
-                            1; // bump
+impl Discriminator for LegacyMachine {
+    const DISCRIMINATOR: &'static [u8] = Machine::DISCRIMINATOR;
+}
+
+impl AccountDeserialize for LegacyMachine {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        check_legacy_discriminator(buf, Self::DISCRIMINATOR)?;
+        // Machines in the current layout never fit in the legacy space
+        require!(buf.len() < Machine::BASE_SPACE, ErrorCode::MachineAlreadyMigrated);
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        deserialize_legacy(buf, Self::DISCRIMINATOR)
+    }
+}
+
+// Migrated machines are written in the current layout instead
+impl AccountSerialize for LegacyMachine {}
+
+impl Owner for LegacyMachine {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+// Left out of the IDL, it would clash with the `Machine` discriminator
+#[cfg(feature = "idl-build")]
+impl anchor_lang::idl::build::IdlBuild for LegacyMachine {}
+
+/// Short-lived key allowed to sign a machine's uploads in place of its device
+/// key, e.g. a hot key kept on the device while the machine is delegated.
+/// Derived from `[b"session", machine, session_key]`.
+#[account]
+pub struct SessionToken {
+    pub machine: Pubkey,
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub created_at: i64,
+    pub expires_at: i64,
+    /// Bitmask of `SCOPE_*` flags
+    pub scope: u8,
+    pub bump: u8,
+}
+
+impl SessionToken {
+    pub const SPACE: usize = 8 + // discriminator
+                            32 + // machine
+                            32 + // owner
+                            32 + // session_key
+                            8 + // created_at
+                            8 + // expires_at
+                            1 + // scope
+                            1; // bump
+
+    /// `upload_data` and `upload_data_batch`
+    pub const SCOPE_UPLOAD: u8 = 1 << 0;
+    pub const ALL_SCOPES: u8 = Self::SCOPE_UPLOAD;
+
+    pub const MAX_DURATION_SECS: i64 = 30 * 24 * 60 * 60;
+
+    /// Check the session is still live and covers `scope`
+    pub fn check(&self, scope: u8, now: i64) -> Result<()> {
+        require!(now < self.expires_at, ErrorCode::SessionExpired);
+        require!(self.scope & scope == scope, ErrorCode::SessionScopeNotAllowed);
+        Ok(())
+    }
+}
+
+#[account]
+pub struct Treasury {
+    pub fees_collected: u64,
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub const SPACE: usize = 8 + // discriminator
+                            8 + // fees_collected
+                            1; // bump
+}
+
+/// Proof that `buyer` purchased entry `entry_index` of the `data` account.
+/// Derived from `[b"receipt", data, entry_index (le bytes), buyer]`.
+#[account]
+pub struct AccessReceipt {
+    pub buyer: Pubkey,
+    pub data: Pubkey,
+    pub entry_index: u64,
+    pub price_paid: u64,
+    pub purchased_at: i64,
+    pub bump: u8,
+}
+
+impl AccessReceipt {
+    pub const SPACE: usize = 8 + // discriminator
+                            32 + // buyer
+                            32 + // data
+                            8 + // entry_index
+                            8 + // price_paid
+                            8 + // purchased_at
+                            1; // bump
+}
+
+#[account]
+pub struct PlantData {
+    pub creator: Pubkey,
+    pub plant_name: String,
+    pub data_count: u64,
+    pub image_count: u64,
+    pub creation_timestamp: i64,
+    pub last_update_timestamp: i64,
+    /// Latest `DataEntry::reading_time` uploaded for this plant
+    pub last_measured_at: i64,
+    pub machine: Pubkey,
+    pub delegation: DelegationRecord,
+    pub bump: u8,
+}
+
+impl PlantData {
+    pub const SPACE: usize = 8 + // discriminator
+                            32 + // creator
+                            (4 + MAX_NAME_LENGTH) + // plant_name
+                            8 + // data_count
+                            8 + // image_count
+                            8 + // creation_timestamp
+                            8 + // last_update_timestamp
+                            8 + // last_measured_at
+                            32 + // machine
+                            DelegationRecord::SPACE + // delegation
+                            1; // bump
+
+    /// Address of the plant named `plant_name` on `machine`
+    pub fn find_address(machine: &Pubkey, plant_name: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"plant", machine.as_ref(), plant_name.as_bytes()],
+            &crate::ID,
+        )
+    }
+
+    /// Address of a plant created before plant names were namespaced by machine
+    pub fn find_legacy_address(plant_name: &str) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"plant", plant_name.as_bytes()], &crate::ID)
+    }
+}
+
+/// `PlantData` as laid out before plants were namespaced by machine, read
+/// only by `migrate_plant`. Legacy accounts carry the `PlantData`
+/// discriminator, so the account traits are implemented by hand instead of
+/// with `#[account]`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyPlantData {
+    pub creator: Pubkey,
+    pub plant_name: String,
+    pub data_count: u64,
+    pub image_count: u64,
+    pub creation_timestamp: i64,
+    pub last_update_timestamp: i64,
+    pub machine: Pubkey,
+    pub bump: u8,
+}
+
+impl Discriminator for LegacyPlantData {
+    const DISCRIMINATOR: &'static [u8] = PlantData::DISCRIMINATOR;
+}
+
+impl AccountDeserialize for LegacyPlantData {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        check_legacy_discriminator(buf, Self::DISCRIMINATOR)?;
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        deserialize_legacy(buf, Self::DISCRIMINATOR)
+    }
+}
+
+// Legacy plants are closed after migration and never written back
+impl AccountSerialize for LegacyPlantData {}
+
+impl Owner for LegacyPlantData {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+// Left out of the IDL, it would clash with the `PlantData` discriminator
+#[cfg(feature = "idl-build")]
+impl anchor_lang::idl::build::IdlBuild for LegacyPlantData {}
+
+/// `IoTData` as laid out before readings moved to data pages, with the
+/// readings stored inline. Read only by `migrate_plant`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyIoTData {
+    pub machine: Pubkey,
+    pub plant: Pubkey,
+    pub data_entries: Vec<LegacyDataEntry>,
+    pub bump: u8,
+}
+
+impl Discriminator for LegacyIoTData {
+    const DISCRIMINATOR: &'static [u8] = IoTData::DISCRIMINATOR;
+}
+
+impl AccountDeserialize for LegacyIoTData {
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        check_legacy_discriminator(buf, Self::DISCRIMINATOR)?;
+        Self::try_deserialize_unchecked(buf)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        deserialize_legacy(buf, Self::DISCRIMINATOR)
+    }
+}
+
+// Migrated headers are written in the current layout instead
+impl AccountSerialize for LegacyIoTData {}
+
+impl Owner for LegacyIoTData {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+// Left out of the IDL, it would clash with the `IoTData` discriminator
+#[cfg(feature = "idl-build")]
+impl anchor_lang::idl::build::IdlBuild for LegacyIoTData {}
+
+/// Reading as stored before fixed-point values and content-addressed images
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyDataEntry {
+    pub timestamp: i64,
+    pub temperature: f64,
+    pub humidity: f64,
+    pub image_url: Option<String>,
+    pub used_count: u64,
+}
+
+impl LegacyDataEntry {
+    /// Convert to the current layout and classify it against `ranges`.
+    /// Values the fixed-point encoding cannot hold are stored as 0 and flagged
+    /// out of range. Image URLs carry no content hash to build an `ImageRef`
+    /// from, so they are dropped.
+    pub fn migrate(&self, ranges: &SensorRanges) -> DataEntry {
+        let encoded = client::encode_temperature(self.temperature)
+            .zip(client::encode_humidity(self.humidity));
+        let (temperature, humidity, quality) = match encoded {
+            Some((temperature, humidity)) => (
+                temperature,
+                humidity,
+                ranges.classify(temperature, humidity).unwrap_or(DataQuality::OutOfRange),
+            ),
+            None => (0, 0, DataQuality::OutOfRange),
+        };
+
+        DataEntry {
+            timestamp: self.timestamp,
+            temperature,
+            humidity,
+            image: None,
+            used_count: self.used_count,
+            quality,
+            metrics: Vec::new(),
+            measured_at: None,
+        }
+    }
+}
+
+/// Legacy accounts share the discriminator of the type that replaced them
+fn check_legacy_discriminator(buf: &[u8], discriminator: &[u8]) -> Result<()> {
+    require!(
+        buf.len() >= discriminator.len(),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorNotFound
+    );
+    require!(
+        &buf[..discriminator.len()] == discriminator,
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(())
+}
+
+/// Read the fields of a legacy account following its discriminator
+fn deserialize_legacy<T: AnchorDeserialize>(buf: &[u8], discriminator: &[u8]) -> Result<T> {
+    let mut data = &buf[discriminator.len()..];
+    T::deserialize(&mut data)
+        .map_err(|_| anchor_lang::error::ErrorCode::AccountDidNotDeserialize.into())
+}
+
+/// Header for the readings of one machine/plant pair. The entries themselves
+/// live in `IoTDataPage` accounts derived from
+/// `[b"data_page", data, page_number (le bytes)]`.
+#[account]
+pub struct IoTData {
+    pub machine: Pubkey,
+    pub plant: Pubkey,
+    pub total_entries: u64,
+    pub delegation: DelegationRecord,
+    pub bump: u8,
+}
+
+#[account]
+pub struct IoTDataPage {
+    pub data: Pubkey,
+    pub page_number: u64,
+    pub data_entries: Vec<DataEntry>,
+    pub delegation: DelegationRecord,
+    pub bump: u8,
+}
+
+/// Reading signed by a machine's device key for `upload_signed_data`.
+/// `timestamp` is the device's measurement time and `image_hash` is the
+/// `content_hash` of the attached image, or all zeros without an image.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SensorPayload {
+    pub machine_id: String,
+    pub plant: Pubkey,
+    pub timestamp: i64,
+    pub temperature: i16,
+    pub humidity: u16,
+    pub image_hash: [u8; 32],
+    pub nonce: u64,
+    /// Extra metrics encoded with `metrics::encode_metrics`
+    pub metrics: Vec<u8>,
+}
+
+/// Quality flag stored with every reading so buyers can filter junk
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DataQuality {
+    Ok,
+    /// Outside the cluster's plausible sensor ranges
+    OutOfRange,
+    /// Plausible but uploaded faster than the upload policy allows
+    Suspect,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DataEntry {
+    pub timestamp: i64,
+    /// Hundredths of a degree Celsius
+    pub temperature: i16,
+    /// Relative humidity in tenths of a percent
+    pub humidity: u16,
+    pub image: Option<ImageRef>,
+    pub used_count: u64,
+    pub quality: DataQuality,
+    /// Extra metrics encoded with `metrics::encode_metrics`
+    pub metrics: Vec<u8>,
+    /// Device-supplied measurement time, when it differs from `timestamp`
+    pub measured_at: Option<i64>,
+}
+
+impl DataEntry {
+    pub const MAX_METRIC_BYTES: usize = 48;
+
+    /// When the reading was taken: the device time if supplied, otherwise
+    /// the time the upload landed on chain
+    pub fn reading_time(&self) -> i64 {
+        self.measured_at.unwrap_or(self.timestamp)
+    }
+}
+
+/// One buffered reading in `upload_data_batch`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct BatchReading {
+    pub measured_at: i64,
+    pub temperature: i16,
+    pub humidity: u16,
+    pub image: Option<ImageRef>,
+    pub metrics: Vec<u8>,
+}
+
+/// Where an image is stored. The locator alone is not trusted; buyers verify
+/// the downloaded bytes against `ImageRef.content_hash`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum ImageStorage {
+    /// IPFS content identifier
+    Ipfs(String),
+    /// Arweave transaction id
+    Arweave([u8; 32]),
+    /// HTTPS URL
+    Https(String),
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImageMime {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+/// Content-addressed reference to an image captured by a machine
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ImageRef {
+    pub storage: ImageStorage,
+    /// SHA-256 of the image bytes
+    pub content_hash: [u8; 32],
+    pub mime: ImageMime,
+    /// Image size in bytes
+    pub size: u32,
+}
+
+impl ImageRef {
+    pub const MAX_LOCATOR_LENGTH: usize = 100;
+
+    pub const SPACE: usize = (1 + 4 + Self::MAX_LOCATOR_LENGTH) + // storage (largest variant)
+                            32 + // content_hash
+                            1 + // mime
+                            4; // size
+
+    pub fn validate(&self) -> Result<()> {
+        match &self.storage {
+            ImageStorage::Ipfs(cid) => {
+                require!(!cid.is_empty(), ErrorCode::InvalidImageRef);
+                require!(cid.len() <= Self::MAX_LOCATOR_LENGTH, ErrorCode::ImageLocatorTooLong);
+            }
+            ImageStorage::Arweave(_) => {}
+            ImageStorage::Https(url) => {
+                require!(url.starts_with("https://"), ErrorCode::InvalidImageRef);
+                require!(url.len() <= Self::MAX_LOCATOR_LENGTH, ErrorCode::ImageLocatorTooLong);
+            }
+        }
+        require!(
+            self.size > 0 && self.content_hash != [0; 32],
+            ErrorCode::InvalidImageRef
+        );
+        Ok(())
+    }
+}
+
+/// Declares how a machine's extra metric is interpreted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MetricSpec {
+    pub metric_id: u8,
+    /// Unit code, see the constants in the `metrics` module
+    pub unit: u8,
+    /// Decimal exponent applied to raw values, e.g. -2 for hundredths
+    pub scale: i8,
 }
 
+impl MetricSpec {
+    pub const SPACE: usize = 1 + // metric_id
+                            1 + // unit
+                            1; // scale
+}
+
+/// Per-machine sensor schema derived from `[b"schema", machine]`
 #[account]
-pub struct PlantData {
-    pub creator: Pubkey,
-    pub plant_name: String,
-    pub data_count: u64,
-    pub image_count: u64,
-    pub creation_timestamp: i64,
-    pub last_update_timestamp: i64,
+pub struct SensorSchema {
     pub machine: Pubkey,
+    pub metrics: Vec<MetricSpec>,
     pub bump: u8,
 }
 
-impl PlantData {
+impl SensorSchema {
+    pub const MAX_METRICS: usize = 16;
+
     pub const SPACE: usize = 8 + // discriminator
-                            32 + // creator
-                            36 + // plant_name (max 32 chars + 4 bytes for length)
-                            8 + // data_count
-                            8 + // image_count
-                            8 + // creation_timestamp
-                            8 + // last_update_timestamp
                             32 + // machine
+                            4 + (MetricSpec::SPACE * Self::MAX_METRICS) + // metrics
                             1; // bump
-}
 
-#[account]
-pub struct IoTData {
-    pub machine: Pubkey,
-    pub plant: Pubkey,
-    pub data_entries: Vec<DataEntry>,
-    pub bump: u8,
-}
+    /// Check encoded extra metrics against the machine's schema, which is
+    /// only required when there are any
+    pub fn check(schema: Option<&SensorSchema>, encoded: &[u8]) -> Result<()> {
+        if encoded.is_empty() {
+            return Ok(());
+        }
+        require!(encoded.len() <= DataEntry::MAX_METRIC_BYTES, ErrorCode::MetricsTooLong);
+        let schema = schema.ok_or(error!(ErrorCode::MissingSensorSchema))?;
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct DataEntry {
-    pub timestamp: i64,
-    pub temperature: f64,
-    pub humidity: f64,
-    pub image_url: Option<String>,
-    pub used_count: u64,
+        let values = metrics::decode_metrics(encoded).ok_or(error!(ErrorCode::InvalidMetricEncoding))?;
+        for (i, value) in values.iter().enumerate() {
+            require!(
+                schema.metrics.iter().any(|spec| spec.metric_id == value.metric_id),
+                ErrorCode::UnknownMetric
+            );
+            require!(
+                !values[..i].iter().any(|other| other.metric_id == value.metric_id),
+                ErrorCode::DuplicateMetric
+            );
+        }
+        Ok(())
+    }
 }
 
 impl IoTData {
+    pub const SPACE: usize = 8 + // discriminator
+                            32 + // machine
+                            32 + // plant
+                            8 + // total_entries
+                            DelegationRecord::SPACE + // delegation
+                            1; // bump
+
+    /// Page number holding the entry with the given global index
+    pub fn page_for(entry_index: u64) -> u64 {
+        entry_index / IoTDataPage::ENTRIES_PER_PAGE as u64
+    }
+
+    /// Page the next uploaded entry will be written to
+    pub fn current_page(&self) -> u64 {
+        Self::page_for(self.total_entries)
+    }
+}
+
+impl IoTDataPage {
+    pub const ENTRIES_PER_PAGE: usize = 40;
+
     pub const BASE_SPACE: usize = 8 + // discriminator
-                             32 + // machine
-                             32 + // plant
+                             32 + // data
+                             8 + // page_number
                              4 + // vec length (u32)
+                             DelegationRecord::SPACE + // delegation
                              1; // bump
                              
     pub const ENTRY_SPACE: usize = 8 + // timestamp
-                               8 + // temperature
-                               8 + // humidity
-                               (1 + 100) + // Option<String>
-                               8; // used_count
-                               
-    pub fn space(max_entries: usize) -> usize {
-        Self::BASE_SPACE + (Self::ENTRY_SPACE * max_entries)
+                               2 + // temperature
+                               2 + // humidity
+                               (1 + ImageRef::SPACE) + // image
+                               8 + // used_count
+                               1 + // quality
+                               (4 + DataEntry::MAX_METRIC_BYTES) + // metrics
+                               (1 + 8); // measured_at
+
+    pub const SPACE: usize = Self::BASE_SPACE + (Self::ENTRY_SPACE * Self::ENTRIES_PER_PAGE);
+
+    /// Position of the entry with the given global index inside its page
+    pub fn slot_for(entry_index: u64) -> usize {
+        (entry_index % Self::ENTRIES_PER_PAGE as u64) as usize
     }
 }
 
@@ -504,15 +2812,232 @@ pub enum ErrorCode {
     InvalidDataEntryIndex,
     #[msg("Plant not linked to the specified machine")]
     PlantNotLinkedToMachine,
+    #[msg("Reward mint has not been initialized")]
+    RewardMintNotInitialized,
+    #[msg("Protocol fee cannot exceed 10000 basis points")]
+    InvalidProtocolFee,
+    #[msg("Insufficient funds in treasury")]
+    InsufficientTreasuryFunds,
+    #[msg("Data account not linked to the specified machine")]
+    DataNotLinkedToMachine,
+    #[msg("Data entry already purchased by this user")]
+    EntryAlreadyPurchased,
+    #[msg("Signer is not the machine's device key")]
+    InvalidDeviceKey,
+    #[msg("Missing or malformed Ed25519 signature instruction")]
+    MissingSignatureInstruction,
+    #[msg("Signed payload does not match the submitted data")]
+    SignedPayloadMismatch,
+    #[msg("Upload nonce must be greater than the machine's last nonce")]
+    StaleNonce,
+    #[msg("Upload arrived before the minimum upload interval elapsed")]
+    UploadTooFrequent,
+    #[msg("Invalid upload policy")]
+    InvalidUploadPolicy,
+    #[msg("Sensor value is physically impossible")]
+    InvalidSensorValue,
+    #[msg("Invalid sensor ranges")]
+    InvalidSensorRanges,
+    #[msg("Too many metrics in sensor schema")]
+    TooManyMetrics,
+    #[msg("Metric declared or reported more than once")]
+    DuplicateMetric,
+    #[msg("Declared metrics cannot be changed or removed")]
+    SchemaChangeNotAllowed,
+    #[msg("Machine has no sensor schema for extra metrics")]
+    MissingSensorSchema,
+    #[msg("Metric is not declared in the machine's sensor schema")]
+    UnknownMetric,
+    #[msg("Malformed metric encoding")]
+    InvalidMetricEncoding,
+    #[msg("Encoded metrics are too long")]
+    MetricsTooLong,
+    #[msg("Upload contains no readings")]
+    EmptyBatch,
+    #[msg("Batch does not fit in the current data page")]
+    BatchExceedsPage,
+    #[msg("Measurement time must be after the plant's previous reading")]
+    MeasurementNotIncreasing,
+    #[msg("Measurement time is in the future")]
+    MeasurementInFuture,
+    #[msg("Measurement time is older than the backfill window")]
+    MeasurementTooOld,
+    #[msg("Invalid image reference")]
+    InvalidImageRef,
+    #[msg("Image locator is too long")]
+    ImageLocatorTooLong,
+    #[msg("Machine ID is too long")]
+    MachineIdTooLong,
+    #[msg("Plant name is too long")]
+    PlantNameTooLong,
+    #[msg("Name cannot be empty")]
+    EmptyName,
+    #[msg("Only the owner can commit before the max commit interval elapses")]
+    CommitNotDue,
+    #[msg("Commit interval cannot be negative")]
+    InvalidCommitInterval,
+    #[msg("Delegation validator cannot be the default pubkey")]
+    InvalidDelegationValidator,
+    #[msg("Commit frequency is outside the allowed range")]
+    InvalidCommitFrequency,
+    #[msg("Session duration is outside the allowed range")]
+    InvalidSessionDuration,
+    #[msg("Session scope is empty or contains unknown flags")]
+    InvalidSessionScope,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Session does not allow this instruction")]
+    SessionScopeNotAllowed,
+    #[msg("Data page is already full")]
+    InvalidPageNumber,
+    #[msg("Every account delegated under the machine must be undelegated with it")]
+    DelegatedAccountsMissing,
+    #[msg("The same account was passed more than once")]
+    DuplicateAccount,
+    #[msg("Machine is already in the current layout")]
+    MachineAlreadyMigrated,
+    #[msg("The plant's data header and first data page are required to migrate its readings")]
+    PlantDataMissing,
 }
 
 #[delegate]
 #[derive(Accounts)]
 pub struct DelegateInput<'info> {
     pub payer: Signer<'info>,
-    /// CHECK The pda to delegate
-    #[account(mut, del)]
-    pub pda: AccountInfo<'info>,
+
+    #[account(seeds = [b"rollup_config"], bump = rollup_config.bump)]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    #[account(
+        mut,
+        del,
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        constraint = cluster.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub cluster: Account<'info, Cluster>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateMachine<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"rollup_config"], bump = rollup_config.bump)]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    #[account(
+        mut,
+        del,
+        constraint = machine.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegatePlant<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"rollup_config"], bump = rollup_config.bump)]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    #[account(
+        mut,
+        constraint = machine.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        mut,
+        del,
+        constraint = plant.machine == machine.key() @ ErrorCode::PlantNotLinkedToMachine
+    )]
+    pub plant: Account<'info, PlantData>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateData<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"rollup_config"], bump = rollup_config.bump)]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    #[account(
+        mut,
+        constraint = machine.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(address = data.plant)]
+    pub plant: Account<'info, PlantData>,
+
+    #[account(
+        mut,
+        del,
+        constraint = data.machine == machine.key() @ ErrorCode::DataNotLinkedToMachine
+    )]
+    pub data: Account<'info, IoTData>,
+}
+
+#[delegate]
+#[derive(Accounts)]
+pub struct DelegateDataPage<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"rollup_config"], bump = rollup_config.bump)]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    #[account(
+        mut,
+        constraint = machine.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        constraint = data.machine == machine.key() @ ErrorCode::DataNotLinkedToMachine
+    )]
+    pub data: Account<'info, IoTData>,
+
+    #[account(
+        mut,
+        del,
+        constraint = page.data == data.key() @ ErrorCode::DataNotLinkedToMachine
+    )]
+    pub page: Account<'info, IoTDataPage>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct CommitMachine<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(seeds = [b"rollup_config"], bump = rollup_config.bump)]
+    pub rollup_config: Account<'info, RollupConfig>,
+
+    #[account(mut)]
+    pub machine: Account<'info, Machine>,
+
+    #[account(
+        mut,
+        constraint = plant.machine == machine.key() @ ErrorCode::PlantNotLinkedToMachine
+    )]
+    pub plant: Account<'info, PlantData>,
+
+    #[account(
+        mut,
+        constraint = data.machine == machine.key() @ ErrorCode::DataNotLinkedToMachine,
+        constraint = data.plant == plant.key() @ ErrorCode::DataNotLinkedToMachine
+    )]
+    pub data: Account<'info, IoTData>,
+
+    #[account(
+        mut,
+        constraint = page.data == data.key() @ ErrorCode::DataNotLinkedToMachine
+    )]
+    pub page: Account<'info, IoTDataPage>,
 }
 
 #[commit]
@@ -520,7 +3045,388 @@ pub struct DelegateInput<'info> {
 pub struct UndelegateAccounts<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    /// CHECK: The PDA to undelegate
+
+    #[account(
+        mut,
+        seeds = [b"cluster"],
+        bump = cluster.bump,
+        constraint = cluster.authority == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub cluster: Account<'info, Cluster>,
+}
+
+#[commit]
+#[derive(Accounts)]
+pub struct UndelegateMachine<'info> {
     #[account(mut)]
-    pub pda: AccountInfo<'info>,
-}
\ No newline at end of file
+    pub payer: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = machine.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub machine: Account<'info, Machine>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Serialized size of an account, including its discriminator
+    fn account_len<T: AccountSerialize>(account: &T) -> usize {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data.len()
+    }
+
+    /// Pseudo-random names up to `max_len` bytes, mixing one to four byte
+    /// characters so byte length and character count differ
+    fn random_names(max_len: usize) -> Vec<String> {
+        const CHARS: [char; 4] = ['a', 'é', '葉', '🌱'];
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        (0..512)
+            .map(|_| {
+                let target = (next() % (max_len as u64 + 1)) as usize;
+                let mut name = String::new();
+                while name.len() < target {
+                    name.push(CHARS[(next() % CHARS.len() as u64) as usize]);
+                }
+                name
+            })
+            .collect()
+    }
+
+    fn machine(machine_id: &str, plants: Vec<(String, Pubkey)>) -> Machine {
+        Machine {
+            owner: Pubkey::new_unique(),
+            device_key: Pubkey::new_unique(),
+            machine_id: machine_id.to_string(),
+            is_active: true,
+            data_count: u64::MAX,
+            image_count: u64::MAX,
+            rewards_earned: u64::MAX,
+            last_data_timestamp: i64::MAX,
+            last_image_timestamp: i64::MAX,
+            data_used_count: u64::MAX,
+            data_price: u64::MAX,
+            last_nonce: u64::MAX,
+            reward_epoch: u64::MAX,
+            epoch_rewards: u64::MAX,
+            last_commit_timestamp: i64::MAX,
+            delegation: DelegationRecord {
+                validator: Some(Pubkey::new_unique()),
+                commit_frequency_ms: u32::MAX,
+                delegated_at: i64::MAX,
+            },
+            delegated_accounts: u32::MAX,
+            plant_count: plants.len() as u64,
+            plants,
+            bump: u8::MAX,
+        }
+    }
+
+    fn plant(machine: Pubkey, plant_name: &str) -> PlantData {
+        PlantData {
+            creator: Pubkey::new_unique(),
+            plant_name: plant_name.to_string(),
+            data_count: u64::MAX,
+            image_count: u64::MAX,
+            creation_timestamp: i64::MAX,
+            last_update_timestamp: i64::MAX,
+            last_measured_at: i64::MAX,
+            machine,
+            delegation: DelegationRecord {
+                validator: Some(Pubkey::new_unique()),
+                commit_frequency_ms: u32::MAX,
+                delegated_at: i64::MAX,
+            },
+            bump: u8::MAX,
+        }
+    }
+
+    fn registry_entry(name: &str) -> RegistryEntry {
+        RegistryEntry {
+            kind: EntityKind::Plant,
+            index: u64::MAX,
+            owner: Pubkey::new_unique(),
+            account: Pubkey::new_unique(),
+            name: name.to_string(),
+            bump: u8::MAX,
+        }
+    }
+
+    #[test]
+    fn name_seed_accepts_exactly_the_names_that_derive_a_pda() {
+        let mut names = random_names(2 * MAX_NAME_LENGTH);
+        names.push(String::new());
+        names.push("a".repeat(MAX_NAME_LENGTH));
+        names.push("a".repeat(MAX_NAME_LENGTH + 1));
+
+        for name in names {
+            let result = name_seed(&name, ErrorCode::PlantNameTooLong);
+            if name.is_empty() {
+                assert_eq!(result.unwrap_err(), ErrorCode::EmptyName.into());
+            } else if name.len() > MAX_NAME_LENGTH {
+                assert_eq!(result.unwrap_err(), ErrorCode::PlantNameTooLong.into());
+            } else {
+                let seed = result.unwrap();
+                assert!(Pubkey::try_find_program_address(&[b"machine", seed], &crate::ID).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn accepted_names_fit_account_space() {
+        let names: Vec<String> = random_names(MAX_NAME_LENGTH)
+            .into_iter()
+            .filter(|name| name_seed(name, ErrorCode::MachineIdTooLong).is_ok())
+            .collect();
+
+        for (i, name) in names.iter().enumerate() {
+            let plants: Vec<(String, Pubkey)> = names[i..]
+                .iter()
+                .take(i % 4)
+                .map(|plant_name| (plant_name.clone(), Pubkey::new_unique()))
+                .collect();
+            let plant_count = plants.len();
+
+            assert!(account_len(&machine(name, plants)) <= Machine::space(plant_count));
+            assert!(account_len(&plant(Pubkey::new_unique(), name)) <= PlantData::SPACE);
+            assert!(account_len(&registry_entry(name)) <= RegistryEntry::SPACE);
+        }
+    }
+
+    #[test]
+    fn max_length_names_fill_account_space_exactly() {
+        let name = "a".repeat(MAX_NAME_LENGTH);
+        let plants = vec![(name.clone(), Pubkey::new_unique()); 3];
+
+        assert_eq!(account_len(&machine(&name, Vec::new())), Machine::SPACE);
+        assert_eq!(account_len(&machine(&name, plants)), Machine::space(3));
+        assert_eq!(account_len(&plant(Pubkey::new_unique(), &name)), PlantData::SPACE);
+        assert_eq!(account_len(&registry_entry(&name)), RegistryEntry::SPACE);
+    }
+
+    #[test]
+    fn plants_of_one_machine_get_their_own_plant_and_data_addresses() {
+        let machine_id = "greenhouse-1";
+        let (machine_key, _) = Machine::find_address(machine_id);
+        let (other_machine_key, _) = Machine::find_address("greenhouse-2");
+        let plant_names: Vec<String> = (0..20).map(|i| format!("tomato-{i}")).collect();
+
+        let mut addresses = std::collections::HashSet::new();
+        for plant_name in &plant_names {
+            let (plant_key, _) = PlantData::find_address(&machine_key, plant_name);
+            let (data_key, _) = Pubkey::find_program_address(
+                &[b"data", machine_id.as_bytes(), plant_name.as_bytes()],
+                &crate::ID,
+            );
+            assert!(addresses.insert(plant_key));
+            assert!(addresses.insert(data_key));
+
+            // The same plant name on another machine is a different plant
+            assert_ne!(PlantData::find_address(&other_machine_key, plant_name).0, plant_key);
+        }
+    }
+
+    #[test]
+    fn machine_space_holds_each_added_plant() {
+        let name = "a".repeat(MAX_NAME_LENGTH);
+        let mut plants = Vec::new();
+
+        for plant_count in 0..=20 {
+            if plant_count > 0 {
+                assert_eq!(
+                    Machine::space(plant_count) - Machine::space(plant_count - 1),
+                    Machine::PLANT_ENTRY_SIZE
+                );
+            }
+            assert_eq!(account_len(&machine(&name, plants.clone())), Machine::space(plant_count));
+            plants.push((name.clone(), Pubkey::new_unique()));
+        }
+    }
+
+    #[test]
+    fn legacy_plant_reads_the_original_layout() {
+        let legacy = LegacyPlantData {
+            creator: Pubkey::new_unique(),
+            plant_name: "tomato-1".to_string(),
+            data_count: 7,
+            image_count: 3,
+            creation_timestamp: 1,
+            last_update_timestamp: 2,
+            machine: Pubkey::new_unique(),
+            bump: 254,
+        };
+        let mut data = PlantData::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+
+        let read = LegacyPlantData::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(read.plant_name, legacy.plant_name);
+        assert_eq!(read.machine, legacy.machine);
+        assert_eq!(read.bump, legacy.bump);
+
+        // Accounts of other types are rejected by their discriminator
+        data[0] ^= 1;
+        assert!(LegacyPlantData::try_deserialize(&mut data.as_slice()).is_err());
+    }
+
+    #[test]
+    fn legacy_machine_reads_only_the_original_layout() {
+        let legacy = LegacyMachine {
+            owner: Pubkey::new_unique(),
+            machine_id: "m".repeat(MAX_NAME_LENGTH),
+            is_active: true,
+            data_count: 7,
+            image_count: 3,
+            rewards_earned: 12,
+            last_data_timestamp: 1,
+            last_image_timestamp: 2,
+            data_used_count: 4,
+            plants: Vec::new(),
+            plant_count: 0,
+            bump: 254,
+        };
+        let mut data = Machine::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+
+        let read = LegacyMachine::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(read.owner, legacy.owner);
+        assert_eq!(read.machine_id, legacy.machine_id);
+        assert_eq!(read.rewards_earned, legacy.rewards_earned);
+        assert_eq!(read.bump, legacy.bump);
+
+        // A migrated machine is rejected instead of misread
+        let mut current = Vec::new();
+        machine("m", Vec::new()).try_serialize(&mut current).unwrap();
+        current.resize(Machine::SPACE, 0);
+        assert_eq!(
+            LegacyMachine::try_deserialize(&mut current.as_slice()).unwrap_err(),
+            ErrorCode::MachineAlreadyMigrated.into()
+        );
+    }
+
+    #[test]
+    fn legacy_readings_migrate_to_fixed_point() {
+        let ranges = SensorRanges::default();
+        let legacy = |temperature: f64, humidity: f64| LegacyDataEntry {
+            timestamp: 1_700_000_000,
+            temperature,
+            humidity,
+            image_url: Some("https://example.com/leaf.jpg".to_string()),
+            used_count: 2,
+        };
+
+        let entry = legacy(21.57, 55.34).migrate(&ranges);
+        assert_eq!(entry.temperature, 2157);
+        assert_eq!(entry.humidity, 553);
+        assert_eq!(entry.quality, DataQuality::Ok);
+        assert_eq!(entry.timestamp, 1_700_000_000);
+        assert_eq!(entry.used_count, 2);
+        assert!(entry.image.is_none());
+        assert_eq!(entry.reading_time(), entry.timestamp);
+
+        assert_eq!(legacy(90.0, 50.0).migrate(&ranges).quality, DataQuality::OutOfRange);
+        for (temperature, humidity) in [(f64::NAN, 50.0), (1e6, 50.0), (20.0, 120.0)] {
+            let entry = legacy(temperature, humidity).migrate(&ranges);
+            assert_eq!((entry.temperature, entry.humidity), (0, 0));
+            assert_eq!(entry.quality, DataQuality::OutOfRange);
+        }
+    }
+
+    #[test]
+    fn legacy_data_header_reads_inline_readings() {
+        let legacy = LegacyIoTData {
+            machine: Pubkey::new_unique(),
+            plant: Pubkey::new_unique(),
+            data_entries: vec![LegacyDataEntry {
+                timestamp: 1,
+                temperature: 20.0,
+                humidity: 40.0,
+                image_url: None,
+                used_count: 0,
+            }],
+            bump: 254,
+        };
+        let mut data = IoTData::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+
+        let read = LegacyIoTData::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(read.plant, legacy.plant);
+        assert_eq!(read.data_entries.len(), 1);
+        assert_eq!(read.bump, legacy.bump);
+
+        // The migrated header fits in the space it is shrunk to
+        let header = IoTData {
+            machine: read.machine,
+            plant: Pubkey::new_unique(),
+            total_entries: u64::MAX,
+            delegation: DelegationRecord {
+                validator: Some(Pubkey::new_unique()),
+                commit_frequency_ms: u32::MAX,
+                delegated_at: i64::MAX,
+            },
+            bump: read.bump,
+        };
+        assert_eq!(account_len(&header), IoTData::SPACE);
+    }
+
+    #[test]
+    fn accepted_image_locators_fit_image_ref_space() {
+        for len in 0..=2 * ImageRef::MAX_LOCATOR_LENGTH {
+            let image = ImageRef {
+                storage: ImageStorage::Ipfs("a".repeat(len)),
+                content_hash: [1; 32],
+                mime: ImageMime::Jpeg,
+                size: 1,
+            };
+
+            let mut data = Vec::new();
+            image.serialize(&mut data).unwrap();
+            let fits = data.len() <= ImageRef::SPACE;
+            assert_eq!(image.validate().is_ok(), len > 0 && fits);
+        }
+    }
+
+    #[test]
+    fn default_upload_policy_bounds_measurement_times() {
+        let policy = UploadPolicy::default();
+        let now = 1_700_000_000;
+
+        assert!(policy.check_measured_at(now, now).is_ok());
+        assert!(policy.check_measured_at(now + UploadPolicy::DEFAULT_MAX_CLOCK_SKEW_SECS, now).is_ok());
+        assert_eq!(
+            policy.check_measured_at(now + UploadPolicy::DEFAULT_MAX_CLOCK_SKEW_SECS + 1, now).unwrap_err(),
+            ErrorCode::MeasurementInFuture.into()
+        );
+        assert!(policy.check_measured_at(now - UploadPolicy::DEFAULT_MAX_BACKFILL_SECS, now).is_ok());
+        assert_eq!(
+            policy.check_measured_at(now - UploadPolicy::DEFAULT_MAX_BACKFILL_SECS - 1, now).unwrap_err(),
+            ErrorCode::MeasurementTooOld.into()
+        );
+        assert_eq!(
+            policy.check_measured_at(1, now).unwrap_err(),
+            ErrorCode::MeasurementTooOld.into()
+        );
+    }
+
+    #[test]
+    fn uploads_carry_one_reading_per_elapsed_interval() {
+        let interval = 300;
+        let last = 1_700_000_000;
+
+        assert_eq!(UploadPolicy::readings_allowed(0, last, interval), 1);
+        assert_eq!(UploadPolicy::readings_allowed(last, last + interval - 1, interval), 0);
+        assert_eq!(UploadPolicy::readings_allowed(last, last + interval, interval), 1);
+        assert_eq!(UploadPolicy::readings_allowed(last, last + 12 * interval + 299, interval), 12);
+        assert_eq!(UploadPolicy::readings_allowed(last, last - 1, interval), 0);
+        assert_eq!(UploadPolicy::readings_allowed(last, last, 0), u64::MAX);
+    }
+}
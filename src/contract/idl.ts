@@ -25,53 +25,22 @@ export type AgroxContract = {
           151,
           80
         ],
-        "accounts": [
-          {
-            "name": "machine",
-            "writable": true
-          },
-          {
-            "name": "user",
-            "signer": true
-          }
-        ],
-        "args": []
-      },
-      {
-        "name": "createPlant",
-        "discriminator": [
-          54,
-          36,
-          254,
-          203,
-          1,
-          141,
-          112,
-          190
-        ],
         "accounts": [
           {
             "name": "cluster",
-            "writable": true
-          },
-          {
-            "name": "plant",
-            "writable": true,
             "pda": {
               "seeds": [
                 {
                   "kind": "const",
                   "value": [
-                    112,
+                    99,
                     108,
-                    97,
-                    110,
-                    116
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
                   ]
-                },
-                {
-                  "kind": "arg",
-                  "path": "plantName"
                 }
               ]
             }
@@ -81,187 +50,142 @@ export type AgroxContract = {
             "writable": true
           },
           {
-            "name": "user",
-            "writable": true,
-            "signer": true
-          },
-          {
-            "name": "systemProgram",
-            "address": "11111111111111111111111111111111"
-          }
-        ],
-        "args": [
-          {
-            "name": "plantName",
-            "type": "string"
-          }
-        ]
-      },
-      {
-        "name": "delegate",
-        "docs": [
-          "Delegate the account to the delegation program"
-        ],
-        "discriminator": [
-          90,
-          147,
-          75,
-          178,
-          85,
-          88,
-          4,
-          137
-        ],
-        "accounts": [
-          {
-            "name": "payer",
-            "signer": true
+            "name": "rewardMint",
+            "writable": true
           },
           {
-            "name": "bufferPda",
-            "writable": true,
+            "name": "rewardAuthority",
+            "docs": [
+              "CHECK: PDA mint authority, validated by its seeds"
+            ],
             "pda": {
               "seeds": [
                 {
                   "kind": "const",
                   "value": [
-                    98,
-                    117,
-                    102,
-                    102,
+                    114,
                     101,
-                    114
-                  ]
-                },
-                {
-                  "kind": "account",
-                  "path": "pda"
-                }
-              ],
-              "program": {
-                "kind": "const",
-                "value": [
-                  56,
-                  153,
-                  144,
-                  62,
-                  95,
-                  5,
-                  162,
-                  244,
-                  127,
-                  68,
-                  213,
-                  47,
-                  238,
-                  243,
-                  85,
-                  253,
-                  209,
-                  56,
-                  22,
-                  10,
-                  190,
-                  179,
-                  206,
-                  102,
-                  246,
-                  43,
-                  128,
-                  125,
-                  209,
-                  240,
-                  23,
-                  139
-                ]
-              }
-            }
-          },
-          {
-            "name": "delegationRecordPda",
-            "writable": true,
-            "pda": {
-              "seeds": [
-                {
-                  "kind": "const",
-                  "value": [
+                    119,
+                    97,
+                    114,
                     100,
-                    101,
-                    108,
-                    101,
-                    103,
+                    95,
                     97,
+                    117,
                     116,
-                    105,
+                    104,
                     111,
-                    110
+                    114,
+                    105,
+                    116,
+                    121
                   ]
-                },
-                {
-                  "kind": "account",
-                  "path": "pda"
                 }
-              ],
-              "program": {
-                "kind": "account",
-                "path": "delegationProgram"
-              }
+              ]
             }
           },
           {
-            "name": "delegationMetadataPda",
+            "name": "userTokenAccount",
             "writable": true,
             "pda": {
               "seeds": [
+                {
+                  "kind": "account",
+                  "path": "user"
+                },
                 {
                   "kind": "const",
                   "value": [
-                    100,
-                    101,
-                    108,
-                    101,
-                    103,
-                    97,
-                    116,
-                    105,
-                    111,
-                    110,
-                    45,
-                    109,
+                    6,
+                    221,
+                    246,
+                    225,
+                    215,
                     101,
-                    116,
-                    97,
-                    100,
-                    97,
-                    116,
-                    97
+                    161,
+                    147,
+                    217,
+                    203,
+                    225,
+                    70,
+                    206,
+                    235,
+                    121,
+                    172,
+                    28,
+                    180,
+                    133,
+                    237,
+                    95,
+                    91,
+                    55,
+                    145,
+                    58,
+                    140,
+                    245,
+                    133,
+                    126,
+                    255,
+                    0,
+                    169
                   ]
                 },
                 {
                   "kind": "account",
-                  "path": "pda"
+                  "path": "rewardMint"
                 }
               ],
               "program": {
-                "kind": "account",
-                "path": "delegationProgram"
+                "kind": "const",
+                "value": [
+                  140,
+                  151,
+                  37,
+                  143,
+                  78,
+                  36,
+                  137,
+                  241,
+                  187,
+                  61,
+                  16,
+                  41,
+                  20,
+                  142,
+                  13,
+                  131,
+                  11,
+                  90,
+                  19,
+                  153,
+                  218,
+                  255,
+                  16,
+                  132,
+                  4,
+                  142,
+                  123,
+                  216,
+                  219,
+                  233,
+                  248,
+                  89
+                ]
               }
             }
           },
           {
-            "name": "pda",
-            "docs": [
-              "CHECK The pda to delegate"
-            ],
-            "writable": true
+            "name": "user",
+            "writable": true,
+            "signer": true
           },
           {
-            "name": "ownerProgram",
-            "address": "4oweKJAgekQk5WoixX6Uagk8SNTbpPZb6QhmYd9Vv6nW"
+            "name": "tokenProgram",
+            "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
           },
           {
-            "name": "delegationProgram",
-            "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+            "name": "associatedTokenProgram",
+            "address": "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL"
           },
           {
             "name": "systemProgram",
@@ -271,130 +195,167 @@ export type AgroxContract = {
         "args": []
       },
       {
-        "name": "initialize",
+        "name": "commit",
+        "docs": [
+          "Checkpoint a delegated machine, plant and its current data page to the",
+          "base layer without undelegating. The owner can commit at any time;",
+          "anyone else once the last commit is older than the rollup config's",
+          "maximum commit interval."
+        ],
         "discriminator": [
-          175,
-          175,
-          109,
-          31,
-          13,
-          152,
-          155,
-          237
+          223,
+          140,
+          142,
+          165,
+          229,
+          208,
+          156,
+          74
         ],
         "accounts": [
           {
-            "name": "cluster",
+            "name": "payer",
             "writable": true,
+            "signer": true
+          },
+          {
+            "name": "rollupConfig",
             "pda": {
               "seeds": [
                 {
                   "kind": "const",
                   "value": [
-                    99,
+                    114,
+                    111,
+                    108,
                     108,
                     117,
-                    115,
-                    116,
-                    101,
-                    114
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
                   ]
                 }
               ]
             }
           },
           {
-            "name": "authority",
-            "writable": true,
-            "signer": true
+            "name": "machine",
+            "writable": true
           },
           {
-            "name": "systemProgram",
-            "address": "11111111111111111111111111111111"
-          }
-        ],
-        "args": []
-      },
-      {
-        "name": "processUndelegation",
-        "discriminator": [
-          196,
-          28,
-          41,
-          206,
-          48,
-          37,
-          51,
-          167
-        ],
-        "accounts": [
-          {
-            "name": "baseAccount",
+            "name": "plant",
             "writable": true
           },
           {
-            "name": "buffer"
+            "name": "data",
+            "writable": true
           },
           {
-            "name": "payer",
+            "name": "page",
             "writable": true
           },
           {
-            "name": "systemProgram"
-          }
-        ],
-        "args": [
+            "name": "magicProgram",
+            "address": "Magic11111111111111111111111111111111111111"
+          },
           {
-            "name": "accountSeeds",
-            "type": {
-              "vec": "bytes"
-            }
+            "name": "magicContext",
+            "writable": true,
+            "address": "MagicContext1111111111111111111111111111111"
           }
-        ]
+        ],
+        "args": []
       },
       {
-        "name": "registerMachine",
+        "name": "createDataPage",
+        "docs": [
+          "Create a data page ahead of time. Pages cannot be created inside the",
+          "ephemeral rollup, so an owner planning to run a plant on the rollup",
+          "creates and delegates the pages it will fill before delegating the",
+          "data header, plant and machine. Several pages can be created in one",
+          "transaction."
+        ],
         "discriminator": [
-          168,
-          160,
-          68,
-          209,
-          28,
-          151,
-          41,
-          17
+          238,
+          88,
+          171,
+          94,
+          32,
+          198,
+          40,
+          128
         ],
         "accounts": [
           {
-            "name": "cluster",
-            "writable": true
+            "name": "machine"
           },
           {
-            "name": "machine",
+            "name": "plant"
+          },
+          {
+            "name": "data",
             "writable": true,
             "pda": {
               "seeds": [
                 {
                   "kind": "const",
                   "value": [
-                    109,
+                    100,
                     97,
-                    99,
-                    104,
-                    105,
-                    110,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine.machine_id",
+                  "account": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "plant.plant_name",
+                  "account": "plantData"
+                }
+              ]
+            }
+          },
+          {
+            "name": "page",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    97,
+                    116,
+                    97,
+                    95,
+                    112,
+                    97,
+                    103,
                     101
                   ]
                 },
+                {
+                  "kind": "account",
+                  "path": "data"
+                },
                 {
                   "kind": "arg",
-                  "path": "machineId"
+                  "path": "pageNumber"
                 }
               ]
             }
           },
           {
-            "name": "user",
+            "name": "owner",
             "writable": true,
             "signer": true
           },
@@ -405,150 +366,149 @@ export type AgroxContract = {
         ],
         "args": [
           {
-            "name": "machineId",
-            "type": "string"
+            "name": "pageNumber",
+            "type": "u64"
           }
         ]
       },
       {
-        "name": "startMachine",
+        "name": "createPlant",
         "discriminator": [
-          121,
-          244,
-          42,
-          69,
+          54,
           36,
-          146,
-          206,
-          127
+          254,
+          203,
+          1,
+          141,
+          112,
+          190
         ],
         "accounts": [
           {
-            "name": "machine",
-            "writable": true
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
           },
           {
-            "name": "user",
-            "signer": true
-          }
-        ],
-        "args": []
-      },
-      {
-        "name": "stopMachine",
-        "discriminator": [
-          191,
-          189,
-          244,
-          250,
-          83,
-          220,
-          186,
-          32
-        ],
-        "accounts": [
+            "name": "registryEntry",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    112,
+                    108,
+                    97,
+                    110,
+                    116,
+                    95,
+                    101,
+                    110,
+                    116,
+                    114,
+                    121
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "cluster.plant_count",
+                  "account": "cluster"
+                }
+              ]
+            }
+          },
           {
             "name": "machine",
             "writable": true
           },
           {
-            "name": "user",
-            "signer": true
-          }
-        ],
-        "args": []
-      },
-      {
-        "name": "undelegate",
-        "docs": [
-          "Undelegate the account from the delegation program"
-        ],
-        "discriminator": [
-          131,
-          148,
-          180,
-          198,
-          91,
-          104,
-          42,
-          238
-        ],
-        "accounts": [
-          {
-            "name": "payer",
-            "writable": true,
-            "signer": true
-          },
-          {
-            "name": "pda",
+            "name": "plant",
             "writable": true
           },
           {
-            "name": "magicProgram",
-            "address": "Magic11111111111111111111111111111111111111"
+            "name": "user",
+            "writable": true,
+            "signer": true
           },
           {
-            "name": "magicContext",
-            "writable": true,
-            "address": "MagicContext1111111111111111111111111111111"
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
           }
         ],
-        "args": []
+        "args": [
+          {
+            "name": "plantName",
+            "type": "string"
+          }
+        ]
       },
       {
-        "name": "uploadData",
+        "name": "createSession",
+        "docs": [
+          "Let `session_key` sign the instructions in `scope` for this machine",
+          "until the session expires. Create sessions before delegating the",
+          "machine, since this reads it on the base layer."
+        ],
         "discriminator": [
-          71,
-          186,
-          8,
-          42,
-          188,
-          75,
-          135,
-          230
+          242,
+          193,
+          143,
+          179,
+          150,
+          25,
+          122,
+          227
         ],
         "accounts": [
           {
-            "name": "cluster",
-            "writable": true
-          },
-          {
-            "name": "machine",
-            "writable": true
-          },
-          {
-            "name": "plant",
-            "writable": true
+            "name": "machine"
           },
           {
-            "name": "data",
+            "name": "session",
             "writable": true,
             "pda": {
               "seeds": [
                 {
                   "kind": "const",
                   "value": [
-                    100,
-                    97,
-                    116,
-                    97
+                    115,
+                    101,
+                    115,
+                    115,
+                    105,
+                    111,
+                    110
                   ]
                 },
                 {
                   "kind": "account",
-                  "path": "machine.machine_id",
-                  "account": "machine"
+                  "path": "machine"
                 },
                 {
-                  "kind": "account",
-                  "path": "plant.plant_name",
-                  "account": "plantData"
+                  "kind": "arg",
+                  "path": "sessionKey"
                 }
               ]
             }
           },
           {
-            "name": "payer",
+            "name": "owner",
             "writable": true,
             "signer": true
           },
@@ -559,174 +519,4006 @@ export type AgroxContract = {
         ],
         "args": [
           {
-            "name": "temperature",
-            "type": "f64"
+            "name": "sessionKey",
+            "type": "pubkey"
           },
           {
-            "name": "humidity",
-            "type": "f64"
+            "name": "durationSecs",
+            "type": "i64"
           },
           {
-            "name": "imageUrl",
-            "type": {
-              "option": "string"
-            }
+            "name": "scope",
+            "type": "u8"
           }
         ]
       },
       {
-        "name": "useData",
-        "discriminator": [
-          245,
-          181,
-          226,
-          28,
-          125,
-          41,
-          221,
-          84
+        "name": "delegate",
+        "docs": [
+          "Delegate the cluster to the ephemeral rollup. Only the cluster",
+          "authority can delegate it."
+        ],
+        "discriminator": [
+          90,
+          147,
+          75,
+          178,
+          85,
+          88,
+          4,
+          137
         ],
         "accounts": [
+          {
+            "name": "payer",
+            "signer": true
+          },
+          {
+            "name": "rollupConfig",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    111,
+                    108,
+                    108,
+                    117,
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "bufferCluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    98,
+                    117,
+                    102,
+                    102,
+                    101,
+                    114
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "cluster"
+                }
+              ],
+              "program": {
+                "kind": "const",
+                "value": [
+                  56,
+                  153,
+                  144,
+                  62,
+                  95,
+                  5,
+                  162,
+                  244,
+                  127,
+                  68,
+                  213,
+                  47,
+                  238,
+                  243,
+                  85,
+                  253,
+                  209,
+                  56,
+                  22,
+                  10,
+                  190,
+                  179,
+                  206,
+                  102,
+                  246,
+                  43,
+                  128,
+                  125,
+                  209,
+                  240,
+                  23,
+                  139
+                ]
+              }
+            }
+          },
+          {
+            "name": "delegationRecordCluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "cluster"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "delegationMetadataCluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110,
+                    45,
+                    109,
+                    101,
+                    116,
+                    97,
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "cluster"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
           {
             "name": "cluster",
-            "writable": true
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
           },
           {
-            "name": "machine",
-            "writable": true
+            "name": "ownerProgram",
+            "address": "4oweKJAgekQk5WoixX6Uagk8SNTbpPZb6QhmYd9Vv6nW"
           },
           {
-            "name": "data",
-            "writable": true
+            "name": "delegationProgram",
+            "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
           },
           {
-            "name": "user",
-            "signer": true
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
           }
         ],
         "args": [
           {
-            "name": "entryIndex",
-            "type": "u64"
+            "name": "validator",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "commitFrequencyMs",
+            "type": "u32"
           }
         ]
-      }
-    ],
-    "accounts": [
-      {
-        "name": "ioTData",
-        "discriminator": [
-          254,
-          141,
-          112,
-          46,
-          177,
-          47,
-          45,
-          83
-        ]
       },
       {
-        "name": "plantData",
+        "name": "delegateData",
+        "docs": [
+          "Delegate the data header of a machine/plant pair to the ephemeral rollup"
+        ],
         "discriminator": [
-          168,
-          139,
-          111,
-          70,
-          1,
-          222,
-          84,
-          115
-        ]
-      }
-    ],
-    "errors": [
-      {
-        "code": 6000,
-        "name": "machineIdAlreadyExists",
-        "msg": "Machine ID already exists"
-      },
-      {
-        "code": 6001,
-        "name": "unauthorized",
-        "msg": "Unauthorized operation"
+          210,
+          213,
+          97,
+          167,
+          43,
+          144,
+          108,
+          186
+        ],
+        "accounts": [
+          {
+            "name": "payer",
+            "signer": true
+          },
+          {
+            "name": "rollupConfig",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    111,
+                    108,
+                    108,
+                    117,
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "plant"
+          },
+          {
+            "name": "bufferData",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    98,
+                    117,
+                    102,
+                    102,
+                    101,
+                    114
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "data"
+                }
+              ],
+              "program": {
+                "kind": "const",
+                "value": [
+                  56,
+                  153,
+                  144,
+                  62,
+                  95,
+                  5,
+                  162,
+                  244,
+                  127,
+                  68,
+                  213,
+                  47,
+                  238,
+                  243,
+                  85,
+                  253,
+                  209,
+                  56,
+                  22,
+                  10,
+                  190,
+                  179,
+                  206,
+                  102,
+                  246,
+                  43,
+                  128,
+                  125,
+                  209,
+                  240,
+                  23,
+                  139
+                ]
+              }
+            }
+          },
+          {
+            "name": "delegationRecordData",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "data"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "delegationMetadataData",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110,
+                    45,
+                    109,
+                    101,
+                    116,
+                    97,
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "data"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "data",
+            "writable": true
+          },
+          {
+            "name": "ownerProgram",
+            "address": "4oweKJAgekQk5WoixX6Uagk8SNTbpPZb6QhmYd9Vv6nW"
+          },
+          {
+            "name": "delegationProgram",
+            "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "validator",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "commitFrequencyMs",
+            "type": "u32"
+          }
+        ]
+      },
+      {
+        "name": "delegateDataPage",
+        "docs": [
+          "Delegate a data page to the ephemeral rollup. New pages cannot be",
+          "created inside the rollup, so create the season's pages up front with",
+          "`create_data_page` and delegate each of them before the data header.",
+          "The header itself may stay on the base layer; `undelegate_machine`",
+          "then takes it as a read-only account."
+        ],
+        "discriminator": [
+          74,
+          225,
+          172,
+          54,
+          157,
+          1,
+          183,
+          214
+        ],
+        "accounts": [
+          {
+            "name": "payer",
+            "signer": true
+          },
+          {
+            "name": "rollupConfig",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    111,
+                    108,
+                    108,
+                    117,
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "data"
+          },
+          {
+            "name": "bufferPage",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    98,
+                    117,
+                    102,
+                    102,
+                    101,
+                    114
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "page"
+                }
+              ],
+              "program": {
+                "kind": "const",
+                "value": [
+                  56,
+                  153,
+                  144,
+                  62,
+                  95,
+                  5,
+                  162,
+                  244,
+                  127,
+                  68,
+                  213,
+                  47,
+                  238,
+                  243,
+                  85,
+                  253,
+                  209,
+                  56,
+                  22,
+                  10,
+                  190,
+                  179,
+                  206,
+                  102,
+                  246,
+                  43,
+                  128,
+                  125,
+                  209,
+                  240,
+                  23,
+                  139
+                ]
+              }
+            }
+          },
+          {
+            "name": "delegationRecordPage",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "page"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "delegationMetadataPage",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110,
+                    45,
+                    109,
+                    101,
+                    116,
+                    97,
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "page"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "page",
+            "writable": true
+          },
+          {
+            "name": "ownerProgram",
+            "address": "4oweKJAgekQk5WoixX6Uagk8SNTbpPZb6QhmYd9Vv6nW"
+          },
+          {
+            "name": "delegationProgram",
+            "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "validator",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "commitFrequencyMs",
+            "type": "u32"
+          }
+        ]
+      },
+      {
+        "name": "delegateMachine",
+        "docs": [
+          "Delegate a machine to the ephemeral rollup. Its data pages, data",
+          "accounts and plants must be delegated first, because those",
+          "instructions read the machine to check ownership and count them in",
+          "`Machine.delegated_accounts`."
+        ],
+        "discriminator": [
+          201,
+          113,
+          190,
+          89,
+          83,
+          131,
+          32,
+          159
+        ],
+        "accounts": [
+          {
+            "name": "payer",
+            "signer": true
+          },
+          {
+            "name": "rollupConfig",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    111,
+                    108,
+                    108,
+                    117,
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "bufferMachine",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    98,
+                    117,
+                    102,
+                    102,
+                    101,
+                    114
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                }
+              ],
+              "program": {
+                "kind": "const",
+                "value": [
+                  56,
+                  153,
+                  144,
+                  62,
+                  95,
+                  5,
+                  162,
+                  244,
+                  127,
+                  68,
+                  213,
+                  47,
+                  238,
+                  243,
+                  85,
+                  253,
+                  209,
+                  56,
+                  22,
+                  10,
+                  190,
+                  179,
+                  206,
+                  102,
+                  246,
+                  43,
+                  128,
+                  125,
+                  209,
+                  240,
+                  23,
+                  139
+                ]
+              }
+            }
+          },
+          {
+            "name": "delegationRecordMachine",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "delegationMetadataMachine",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110,
+                    45,
+                    109,
+                    101,
+                    116,
+                    97,
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "ownerProgram",
+            "address": "4oweKJAgekQk5WoixX6Uagk8SNTbpPZb6QhmYd9Vv6nW"
+          },
+          {
+            "name": "delegationProgram",
+            "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "validator",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "commitFrequencyMs",
+            "type": "u32"
+          }
+        ]
+      },
+      {
+        "name": "delegatePlant",
+        "docs": [
+          "Delegate a plant of the payer's machine to the ephemeral rollup"
+        ],
+        "discriminator": [
+          177,
+          206,
+          1,
+          117,
+          233,
+          236,
+          61,
+          162
+        ],
+        "accounts": [
+          {
+            "name": "payer",
+            "signer": true
+          },
+          {
+            "name": "rollupConfig",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    111,
+                    108,
+                    108,
+                    117,
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "bufferPlant",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    98,
+                    117,
+                    102,
+                    102,
+                    101,
+                    114
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "plant"
+                }
+              ],
+              "program": {
+                "kind": "const",
+                "value": [
+                  56,
+                  153,
+                  144,
+                  62,
+                  95,
+                  5,
+                  162,
+                  244,
+                  127,
+                  68,
+                  213,
+                  47,
+                  238,
+                  243,
+                  85,
+                  253,
+                  209,
+                  56,
+                  22,
+                  10,
+                  190,
+                  179,
+                  206,
+                  102,
+                  246,
+                  43,
+                  128,
+                  125,
+                  209,
+                  240,
+                  23,
+                  139
+                ]
+              }
+            }
+          },
+          {
+            "name": "delegationRecordPlant",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "plant"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "delegationMetadataPlant",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    101,
+                    108,
+                    101,
+                    103,
+                    97,
+                    116,
+                    105,
+                    111,
+                    110,
+                    45,
+                    109,
+                    101,
+                    116,
+                    97,
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "plant"
+                }
+              ],
+              "program": {
+                "kind": "account",
+                "path": "delegationProgram"
+              }
+            }
+          },
+          {
+            "name": "plant",
+            "writable": true
+          },
+          {
+            "name": "ownerProgram",
+            "address": "4oweKJAgekQk5WoixX6Uagk8SNTbpPZb6QhmYd9Vv6nW"
+          },
+          {
+            "name": "delegationProgram",
+            "address": "DELeGGvXpWV2fqJUhqcF5ZSYMS4JTLjteaAMARRSaeSh"
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "validator",
+            "type": {
+              "option": "pubkey"
+            }
+          },
+          {
+            "name": "commitFrequencyMs",
+            "type": "u32"
+          }
+        ]
+      },
+      {
+        "name": "initialize",
+        "discriminator": [
+          175,
+          175,
+          109,
+          31,
+          13,
+          152,
+          155,
+          237
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "initializeRewardMint",
+        "docs": [
+          "Create the SPL mint used to pay out machine rewards. The mint authority",
+          "is the `reward_authority` PDA, so only this program can mint."
+        ],
+        "discriminator": [
+          136,
+          219,
+          113,
+          48,
+          109,
+          59,
+          18,
+          208
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "rewardMint",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    101,
+                    119,
+                    97,
+                    114,
+                    100,
+                    95,
+                    109,
+                    105,
+                    110,
+                    116
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "rewardAuthority",
+            "docs": [
+              "CHECK: PDA that only acts as the mint authority of `reward_mint`"
+            ],
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    101,
+                    119,
+                    97,
+                    114,
+                    100,
+                    95,
+                    97,
+                    117,
+                    116,
+                    104,
+                    111,
+                    114,
+                    105,
+                    116,
+                    121
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "writable": true,
+            "signer": true,
+            "relations": [
+              "cluster"
+            ]
+          },
+          {
+            "name": "tokenProgram",
+            "address": "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "initializeRollupConfig",
+        "docs": [
+          "Create the rollup config PDA. It is never delegated, so delegate and",
+          "commit instructions can read it on the base layer and in the rollup."
+        ],
+        "discriminator": [
+          48,
+          16,
+          86,
+          43,
+          158,
+          78,
+          63,
+          125
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "rollupConfig",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    111,
+                    108,
+                    108,
+                    117,
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "writable": true,
+            "signer": true,
+            "relations": [
+              "cluster"
+            ]
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "maxCommitIntervalSecs",
+            "type": "i64"
+          }
+        ]
+      },
+      {
+        "name": "initializeTreasury",
+        "docs": [
+          "Create the treasury PDA that collects protocol fees from data sales"
+        ],
+        "discriminator": [
+          124,
+          186,
+          211,
+          195,
+          85,
+          165,
+          129,
+          166
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "treasury",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    116,
+                    114,
+                    101,
+                    97,
+                    115,
+                    117,
+                    114,
+                    121
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "writable": true,
+            "signer": true,
+            "relations": [
+              "cluster"
+            ]
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "protocolFeeBps",
+            "type": "u16"
+          }
+        ]
+      },
+      {
+        "name": "migrateMachine",
+        "docs": [
+          "Rewrite a machine registered before device keys and the fields that",
+          "followed them into the current layout, growing the account to fit.",
+          "The owner stays the device key until `set_device_key` is called. Run",
+          "this before migrating the machine's plants."
+        ],
+        "discriminator": [
+          158,
+          0,
+          135,
+          207,
+          226,
+          252,
+          112,
+          150
+        ],
+        "accounts": [
+          {
+            "name": "machine",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    109,
+                    97,
+                    99,
+                    104,
+                    105,
+                    110,
+                    101
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine.machine_id",
+                  "account": "legacyMachine"
+                }
+              ]
+            }
+          },
+          {
+            "name": "user",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "migratePlant",
+        "docs": [
+          "Move a plant from the legacy `[b\"plant\", plant_name]` address to its",
+          "machine-namespaced address and close the legacy account. Its machine",
+          "must already have been migrated with `migrate_machine`."
+        ],
+        "discriminator": [
+          78,
+          120,
+          249,
+          161,
+          209,
+          246,
+          15,
+          167
+        ],
+        "accounts": [
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "legacyPlant",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    112,
+                    108,
+                    97,
+                    110,
+                    116
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "legacy_plant.plant_name",
+                  "account": "legacyPlantData"
+                }
+              ]
+            }
+          },
+          {
+            "name": "plant",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    112,
+                    108,
+                    97,
+                    110,
+                    116
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "legacy_plant.plant_name",
+                  "account": "legacyPlantData"
+                }
+              ]
+            }
+          },
+          {
+            "name": "cluster",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "data",
+            "docs": [
+              "Legacy data header of the plant, required if it has any uploads.",
+              "Shrunk to the current header size, which keeps its address."
+            ],
+            "writable": true,
+            "optional": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine.machine_id",
+                  "account": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "legacy_plant.plant_name",
+                  "account": "legacyPlantData"
+                }
+              ]
+            }
+          },
+          {
+            "name": "page",
+            "docs": [
+              "First data page of the plant, receives the legacy readings. Required",
+              "along with `data`."
+            ],
+            "writable": true,
+            "optional": true
+          },
+          {
+            "name": "registryEntry",
+            "docs": [
+              "Registry entry of the plant, if it was registered after the cluster",
+              "registry was introduced"
+            ],
+            "writable": true,
+            "optional": true
+          },
+          {
+            "name": "user",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "processUndelegation",
+        "discriminator": [
+          196,
+          28,
+          41,
+          206,
+          48,
+          37,
+          51,
+          167
+        ],
+        "accounts": [
+          {
+            "name": "baseAccount",
+            "writable": true
+          },
+          {
+            "name": "buffer"
+          },
+          {
+            "name": "payer",
+            "writable": true
+          },
+          {
+            "name": "systemProgram"
+          }
+        ],
+        "args": [
+          {
+            "name": "accountSeeds",
+            "type": {
+              "vec": "bytes"
+            }
+          }
+        ]
+      },
+      {
+        "name": "registerMachine",
+        "discriminator": [
+          168,
+          160,
+          68,
+          209,
+          28,
+          151,
+          41,
+          17
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "registryEntry",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    109,
+                    97,
+                    99,
+                    104,
+                    105,
+                    110,
+                    101,
+                    95,
+                    101,
+                    110,
+                    116,
+                    114,
+                    121
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "cluster.machine_count",
+                  "account": "cluster"
+                }
+              ]
+            }
+          },
+          {
+            "name": "user",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "machineId",
+            "type": "string"
+          }
+        ]
+      },
+      {
+        "name": "revokeSession",
+        "docs": [
+          "Revoke a session and return its rent to the owner"
+        ],
+        "discriminator": [
+          86,
+          92,
+          198,
+          120,
+          144,
+          2,
+          7,
+          194
+        ],
+        "accounts": [
+          {
+            "name": "session",
+            "writable": true
+          },
+          {
+            "name": "owner",
+            "writable": true,
+            "signer": true,
+            "relations": [
+              "session"
+            ]
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "setDataPrice",
+        "discriminator": [
+          39,
+          188,
+          15,
+          22,
+          103,
+          46,
+          90,
+          125
+        ],
+        "accounts": [
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "user",
+            "signer": true
+          }
+        ],
+        "args": [
+          {
+            "name": "dataPrice",
+            "type": "u64"
+          }
+        ]
+      },
+      {
+        "name": "setDeviceKey",
+        "docs": [
+          "Set the hardware key allowed to sign uploads for this machine"
+        ],
+        "discriminator": [
+          151,
+          171,
+          217,
+          174,
+          60,
+          25,
+          94,
+          228
+        ],
+        "accounts": [
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "user",
+            "signer": true
+          }
+        ],
+        "args": [
+          {
+            "name": "deviceKey",
+            "type": "pubkey"
+          }
+        ]
+      },
+      {
+        "name": "setMaxCommitInterval",
+        "docs": [
+          "Set how stale delegated machine state may get before anyone can",
+          "checkpoint it with `commit`, 0 to only allow owners to commit"
+        ],
+        "discriminator": [
+          158,
+          245,
+          132,
+          84,
+          71,
+          148,
+          157,
+          90
+        ],
+        "accounts": [
+          {
+            "name": "rollupConfig",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    111,
+                    108,
+                    108,
+                    117,
+                    112,
+                    95,
+                    99,
+                    111,
+                    110,
+                    102,
+                    105,
+                    103
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "signer": true,
+            "relations": [
+              "rollupConfig"
+            ]
+          }
+        ],
+        "args": [
+          {
+            "name": "maxCommitIntervalSecs",
+            "type": "i64"
+          }
+        ]
+      },
+      {
+        "name": "setProtocolFee",
+        "discriminator": [
+          173,
+          239,
+          83,
+          242,
+          136,
+          43,
+          144,
+          217
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "signer": true,
+            "relations": [
+              "cluster"
+            ]
+          }
+        ],
+        "args": [
+          {
+            "name": "protocolFeeBps",
+            "type": "u16"
+          }
+        ]
+      },
+      {
+        "name": "setSensorRanges",
+        "discriminator": [
+          193,
+          94,
+          56,
+          182,
+          239,
+          138,
+          63,
+          188
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "signer": true,
+            "relations": [
+              "cluster"
+            ]
+          }
+        ],
+        "args": [
+          {
+            "name": "sensorRanges",
+            "type": {
+              "defined": {
+                "name": "sensorRanges"
+              }
+            }
+          }
+        ]
+      },
+      {
+        "name": "setSensorSchema",
+        "docs": [
+          "Declare the extra metrics this machine reports. Metrics can be added",
+          "later, but a declared metric's unit and scale can never change so that",
+          "previously uploaded readings keep their meaning."
+        ],
+        "discriminator": [
+          188,
+          78,
+          182,
+          200,
+          36,
+          26,
+          44,
+          131
+        ],
+        "accounts": [
+          {
+            "name": "machine"
+          },
+          {
+            "name": "schema",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    115,
+                    99,
+                    104,
+                    101,
+                    109,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                }
+              ]
+            }
+          },
+          {
+            "name": "user",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "metrics",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "metricSpec"
+                }
+              }
+            }
+          }
+        ]
+      },
+      {
+        "name": "setUploadPolicy",
+        "discriminator": [
+          199,
+          81,
+          249,
+          201,
+          50,
+          13,
+          58,
+          16
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "signer": true,
+            "relations": [
+              "cluster"
+            ]
+          }
+        ],
+        "args": [
+          {
+            "name": "uploadPolicy",
+            "type": {
+              "defined": {
+                "name": "uploadPolicy"
+              }
+            }
+          }
+        ]
+      },
+      {
+        "name": "startMachine",
+        "discriminator": [
+          121,
+          244,
+          42,
+          69,
+          36,
+          146,
+          206,
+          127
+        ],
+        "accounts": [
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "user",
+            "signer": true
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "stopMachine",
+        "discriminator": [
+          191,
+          189,
+          244,
+          250,
+          83,
+          220,
+          186,
+          32
+        ],
+        "accounts": [
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "user",
+            "signer": true
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "undelegate",
+        "docs": [
+          "Commit and undelegate the cluster. Only the cluster authority can",
+          "undelegate it."
+        ],
+        "discriminator": [
+          131,
+          148,
+          180,
+          198,
+          91,
+          104,
+          42,
+          238
+        ],
+        "accounts": [
+          {
+            "name": "payer",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "cluster",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "magicProgram",
+            "address": "Magic11111111111111111111111111111111111111"
+          },
+          {
+            "name": "magicContext",
+            "writable": true,
+            "address": "MagicContext1111111111111111111111111111111"
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "undelegateMachine",
+        "docs": [
+          "Commit and undelegate a machine along with every plant, data header",
+          "and data page delegated under it. Those are passed as the first",
+          "`Machine.delegated_accounts` remaining accounts, so none are left",
+          "behind under the delegation program. Data headers of delegated pages",
+          "that were not delegated themselves follow them and are only read to",
+          "tie the pages to the machine. Only the machine owner can undelegate."
+        ],
+        "discriminator": [
+          234,
+          56,
+          241,
+          187,
+          1,
+          106,
+          15,
+          192
+        ],
+        "accounts": [
+          {
+            "name": "payer",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "magicProgram",
+            "address": "Magic11111111111111111111111111111111111111"
+          },
+          {
+            "name": "magicContext",
+            "writable": true,
+            "address": "MagicContext1111111111111111111111111111111"
+          }
+        ],
+        "args": []
+      },
+      {
+        "name": "uploadData",
+        "discriminator": [
+          71,
+          186,
+          8,
+          42,
+          188,
+          75,
+          135,
+          230
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "plant",
+            "writable": true
+          },
+          {
+            "name": "data",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine.machine_id",
+                  "account": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "plant.plant_name",
+                  "account": "plantData"
+                }
+              ]
+            }
+          },
+          {
+            "name": "page",
+            "docs": [
+              "Page holding the next entry. A new page is created automatically",
+              "once the previous one is full."
+            ],
+            "writable": true
+          },
+          {
+            "name": "schema",
+            "docs": [
+              "Required when the upload carries extra metrics"
+            ],
+            "optional": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    115,
+                    99,
+                    104,
+                    101,
+                    109,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                }
+              ]
+            }
+          },
+          {
+            "name": "device",
+            "docs": [
+              "Machine's device key, or a session key when `session` is passed;",
+              "may be the same account as `payer`"
+            ],
+            "signer": true
+          },
+          {
+            "name": "session",
+            "docs": [
+              "Session letting `device` upload in place of the device key"
+            ],
+            "optional": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    115,
+                    101,
+                    115,
+                    115,
+                    105,
+                    111,
+                    110
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "device"
+                }
+              ]
+            }
+          },
+          {
+            "name": "payer",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "temperature",
+            "type": "i16"
+          },
+          {
+            "name": "humidity",
+            "type": "u16"
+          },
+          {
+            "name": "image",
+            "type": {
+              "option": {
+                "defined": {
+                  "name": "imageRef"
+                }
+              }
+            }
+          },
+          {
+            "name": "nonce",
+            "type": "u64"
+          },
+          {
+            "name": "metrics",
+            "type": "bytes"
+          },
+          {
+            "name": "measuredAt",
+            "type": {
+              "option": "i64"
+            }
+          }
+        ]
+      },
+      {
+        "name": "uploadDataBatch",
+        "docs": [
+          "Upload readings buffered by the device while offline in one",
+          "transaction. Readings carry their measurement time, must be in",
+          "chronological order and must fit in the current data page."
+        ],
+        "discriminator": [
+          193,
+          41,
+          92,
+          118,
+          75,
+          197,
+          104,
+          37
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "plant",
+            "writable": true
+          },
+          {
+            "name": "data",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine.machine_id",
+                  "account": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "plant.plant_name",
+                  "account": "plantData"
+                }
+              ]
+            }
+          },
+          {
+            "name": "page",
+            "docs": [
+              "Page holding the next entry. A new page is created automatically",
+              "once the previous one is full."
+            ],
+            "writable": true
+          },
+          {
+            "name": "schema",
+            "docs": [
+              "Required when the upload carries extra metrics"
+            ],
+            "optional": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    115,
+                    99,
+                    104,
+                    101,
+                    109,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                }
+              ]
+            }
+          },
+          {
+            "name": "device",
+            "docs": [
+              "Machine's device key, or a session key when `session` is passed;",
+              "may be the same account as `payer`"
+            ],
+            "signer": true
+          },
+          {
+            "name": "session",
+            "docs": [
+              "Session letting `device` upload in place of the device key"
+            ],
+            "optional": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    115,
+                    101,
+                    115,
+                    115,
+                    105,
+                    111,
+                    110
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "device"
+                }
+              ]
+            }
+          },
+          {
+            "name": "payer",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "readings",
+            "type": {
+              "vec": {
+                "defined": {
+                  "name": "batchReading"
+                }
+              }
+            }
+          },
+          {
+            "name": "nonce",
+            "type": "u64"
+          }
+        ]
+      },
+      {
+        "name": "uploadSignedData",
+        "docs": [
+          "Upload a reading signed by the machine's device key and submitted by",
+          "any relayer. The transaction must carry an Ed25519 program instruction",
+          "right before this one that verifies the device signature over the",
+          "borsh-serialized `payload`."
+        ],
+        "discriminator": [
+          165,
+          122,
+          20,
+          35,
+          91,
+          162,
+          150,
+          131
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "plant",
+            "writable": true
+          },
+          {
+            "name": "data",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    100,
+                    97,
+                    116,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine.machine_id",
+                  "account": "machine"
+                },
+                {
+                  "kind": "account",
+                  "path": "plant.plant_name",
+                  "account": "plantData"
+                }
+              ]
+            }
+          },
+          {
+            "name": "page",
+            "writable": true
+          },
+          {
+            "name": "schema",
+            "docs": [
+              "Required when the payload carries extra metrics"
+            ],
+            "optional": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    115,
+                    99,
+                    104,
+                    101,
+                    109,
+                    97
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "machine"
+                }
+              ]
+            }
+          },
+          {
+            "name": "payer",
+            "docs": [
+              "Relayer paying for the transaction"
+            ],
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "instructions",
+            "docs": [
+              "CHECK: Instructions sysvar, checked by address"
+            ],
+            "address": "Sysvar1nstructions1111111111111111111111111"
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "payload",
+            "type": {
+              "defined": {
+                "name": "sensorPayload"
+              }
+            }
+          },
+          {
+            "name": "image",
+            "type": {
+              "option": {
+                "defined": {
+                  "name": "imageRef"
+                }
+              }
+            }
+          }
+        ]
+      },
+      {
+        "name": "useData",
+        "discriminator": [
+          245,
+          181,
+          226,
+          28,
+          125,
+          41,
+          221,
+          84
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "writable": true
+          },
+          {
+            "name": "machine",
+            "writable": true
+          },
+          {
+            "name": "data"
+          },
+          {
+            "name": "page",
+            "writable": true
+          },
+          {
+            "name": "owner",
+            "docs": [
+              "Machine owner receiving the payment"
+            ],
+            "writable": true
+          },
+          {
+            "name": "treasury",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    116,
+                    114,
+                    101,
+                    97,
+                    115,
+                    117,
+                    114,
+                    121
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "receipt",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    114,
+                    101,
+                    99,
+                    101,
+                    105,
+                    112,
+                    116
+                  ]
+                },
+                {
+                  "kind": "account",
+                  "path": "data"
+                },
+                {
+                  "kind": "arg",
+                  "path": "entryIndex"
+                },
+                {
+                  "kind": "account",
+                  "path": "user"
+                }
+              ]
+            }
+          },
+          {
+            "name": "user",
+            "writable": true,
+            "signer": true
+          },
+          {
+            "name": "systemProgram",
+            "address": "11111111111111111111111111111111"
+          }
+        ],
+        "args": [
+          {
+            "name": "entryIndex",
+            "type": "u64"
+          }
+        ]
+      },
+      {
+        "name": "withdrawTreasury",
+        "discriminator": [
+          40,
+          63,
+          122,
+          158,
+          144,
+          216,
+          83,
+          96
+        ],
+        "accounts": [
+          {
+            "name": "cluster",
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    99,
+                    108,
+                    117,
+                    115,
+                    116,
+                    101,
+                    114
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "treasury",
+            "writable": true,
+            "pda": {
+              "seeds": [
+                {
+                  "kind": "const",
+                  "value": [
+                    116,
+                    114,
+                    101,
+                    97,
+                    115,
+                    117,
+                    114,
+                    121
+                  ]
+                }
+              ]
+            }
+          },
+          {
+            "name": "authority",
+            "writable": true,
+            "signer": true,
+            "relations": [
+              "cluster"
+            ]
+          }
+        ],
+        "args": [
+          {
+            "name": "amount",
+            "type": "u64"
+          }
+        ]
+      }
+    ],
+    "accounts": [
+      {
+        "name": "accessReceipt",
+        "discriminator": [
+          160,
+          208,
+          81,
+          74,
+          140,
+          203,
+          172,
+          176
+        ]
+      },
+      {
+        "name": "cluster",
+        "discriminator": [
+          236,
+          225,
+          118,
+          228,
+          173,
+          106,
+          18,
+          60
+        ]
+      },
+      {
+        "name": "ioTData",
+        "discriminator": [
+          254,
+          141,
+          112,
+          46,
+          177,
+          47,
+          45,
+          83
+        ]
+      },
+      {
+        "name": "ioTDataPage",
+        "discriminator": [
+          149,
+          212,
+          161,
+          22,
+          136,
+          86,
+          198,
+          131
+        ]
+      },
+      {
+        "name": "plantData",
+        "discriminator": [
+          168,
+          139,
+          111,
+          70,
+          1,
+          222,
+          84,
+          115
+        ]
+      },
+      {
+        "name": "registryEntry",
+        "discriminator": [
+          48,
+          198,
+          240,
+          252,
+          155,
+          186,
+          72,
+          16
+        ]
+      },
+      {
+        "name": "rollupConfig",
+        "discriminator": [
+          177,
+          255,
+          142,
+          196,
+          232,
+          168,
+          122,
+          162
+        ]
+      },
+      {
+        "name": "sensorSchema",
+        "discriminator": [
+          181,
+          105,
+          181,
+          26,
+          7,
+          18,
+          19,
+          41
+        ]
+      },
+      {
+        "name": "sessionToken",
+        "discriminator": [
+          233,
+          4,
+          115,
+          14,
+          46,
+          21,
+          1,
+          15
+        ]
+      },
+      {
+        "name": "treasury",
+        "discriminator": [
+          238,
+          239,
+          123,
+          238,
+          89,
+          1,
+          168,
+          253
+        ]
+      }
+    ],
+    "errors": [
+      {
+        "code": 6000,
+        "name": "machineIdAlreadyExists",
+        "msg": "Machine ID already exists"
+      },
+      {
+        "code": 6001,
+        "name": "unauthorized",
+        "msg": "Unauthorized operation"
+      },
+      {
+        "code": 6002,
+        "name": "machineNotActive",
+        "msg": "Machine is not active"
+      },
+      {
+        "code": 6003,
+        "name": "noRewardsAvailable",
+        "msg": "No rewards available to claim"
+      },
+      {
+        "code": 6004,
+        "name": "unregisteredPlant",
+        "msg": "Unregistered plant"
+      },
+      {
+        "code": 6005,
+        "name": "invalidDataEntryIndex",
+        "msg": "Invalid data entry index"
+      },
+      {
+        "code": 6006,
+        "name": "plantNotLinkedToMachine",
+        "msg": "Plant not linked to the specified machine"
+      },
+      {
+        "code": 6007,
+        "name": "rewardMintNotInitialized",
+        "msg": "Reward mint has not been initialized"
+      },
+      {
+        "code": 6008,
+        "name": "invalidProtocolFee",
+        "msg": "Protocol fee cannot exceed 10000 basis points"
+      },
+      {
+        "code": 6009,
+        "name": "insufficientTreasuryFunds",
+        "msg": "Insufficient funds in treasury"
+      },
+      {
+        "code": 6010,
+        "name": "dataNotLinkedToMachine",
+        "msg": "Data account not linked to the specified machine"
+      },
+      {
+        "code": 6011,
+        "name": "entryAlreadyPurchased",
+        "msg": "Data entry already purchased by this user"
+      },
+      {
+        "code": 6012,
+        "name": "invalidDeviceKey",
+        "msg": "Signer is not the machine's device key"
+      },
+      {
+        "code": 6013,
+        "name": "missingSignatureInstruction",
+        "msg": "Missing or malformed Ed25519 signature instruction"
+      },
+      {
+        "code": 6014,
+        "name": "signedPayloadMismatch",
+        "msg": "Signed payload does not match the submitted data"
+      },
+      {
+        "code": 6015,
+        "name": "staleNonce",
+        "msg": "Upload nonce must be greater than the machine's last nonce"
+      },
+      {
+        "code": 6016,
+        "name": "uploadTooFrequent",
+        "msg": "Upload arrived before the minimum upload interval elapsed"
+      },
+      {
+        "code": 6017,
+        "name": "invalidUploadPolicy",
+        "msg": "Invalid upload policy"
+      },
+      {
+        "code": 6018,
+        "name": "invalidSensorValue",
+        "msg": "Sensor value is physically impossible"
+      },
+      {
+        "code": 6019,
+        "name": "invalidSensorRanges",
+        "msg": "Invalid sensor ranges"
+      },
+      {
+        "code": 6020,
+        "name": "tooManyMetrics",
+        "msg": "Too many metrics in sensor schema"
+      },
+      {
+        "code": 6021,
+        "name": "duplicateMetric",
+        "msg": "Metric declared or reported more than once"
+      },
+      {
+        "code": 6022,
+        "name": "schemaChangeNotAllowed",
+        "msg": "Declared metrics cannot be changed or removed"
+      },
+      {
+        "code": 6023,
+        "name": "missingSensorSchema",
+        "msg": "Machine has no sensor schema for extra metrics"
+      },
+      {
+        "code": 6024,
+        "name": "unknownMetric",
+        "msg": "Metric is not declared in the machine's sensor schema"
+      },
+      {
+        "code": 6025,
+        "name": "invalidMetricEncoding",
+        "msg": "Malformed metric encoding"
+      },
+      {
+        "code": 6026,
+        "name": "metricsTooLong",
+        "msg": "Encoded metrics are too long"
+      },
+      {
+        "code": 6027,
+        "name": "emptyBatch",
+        "msg": "Upload contains no readings"
+      },
+      {
+        "code": 6028,
+        "name": "batchExceedsPage",
+        "msg": "Batch does not fit in the current data page"
+      },
+      {
+        "code": 6029,
+        "name": "measurementNotIncreasing",
+        "msg": "Measurement time must be after the plant's previous reading"
+      },
+      {
+        "code": 6030,
+        "name": "measurementInFuture",
+        "msg": "Measurement time is in the future"
+      },
+      {
+        "code": 6031,
+        "name": "measurementTooOld",
+        "msg": "Measurement time is older than the backfill window"
+      },
+      {
+        "code": 6032,
+        "name": "invalidImageRef",
+        "msg": "Invalid image reference"
+      },
+      {
+        "code": 6033,
+        "name": "imageLocatorTooLong",
+        "msg": "Image locator is too long"
+      },
+      {
+        "code": 6034,
+        "name": "machineIdTooLong",
+        "msg": "Machine ID is too long"
+      },
+      {
+        "code": 6035,
+        "name": "plantNameTooLong",
+        "msg": "Plant name is too long"
+      },
+      {
+        "code": 6036,
+        "name": "emptyName",
+        "msg": "Name cannot be empty"
+      },
+      {
+        "code": 6037,
+        "name": "commitNotDue",
+        "msg": "Only the owner can commit before the max commit interval elapses"
+      },
+      {
+        "code": 6038,
+        "name": "invalidCommitInterval",
+        "msg": "Commit interval cannot be negative"
+      },
+      {
+        "code": 6039,
+        "name": "invalidDelegationValidator",
+        "msg": "Delegation validator cannot be the default pubkey"
+      },
+      {
+        "code": 6040,
+        "name": "invalidCommitFrequency",
+        "msg": "Commit frequency is outside the allowed range"
+      },
+      {
+        "code": 6041,
+        "name": "invalidSessionDuration",
+        "msg": "Session duration is outside the allowed range"
+      },
+      {
+        "code": 6042,
+        "name": "invalidSessionScope",
+        "msg": "Session scope is empty or contains unknown flags"
+      },
+      {
+        "code": 6043,
+        "name": "sessionExpired",
+        "msg": "Session has expired"
+      },
+      {
+        "code": 6044,
+        "name": "sessionScopeNotAllowed",
+        "msg": "Session does not allow this instruction"
+      },
+      {
+        "code": 6045,
+        "name": "invalidPageNumber",
+        "msg": "Data page is already full"
+      },
+      {
+        "code": 6046,
+        "name": "delegatedAccountsMissing",
+        "msg": "Every account delegated under the machine must be undelegated with it"
+      },
+      {
+        "code": 6047,
+        "name": "duplicateAccount",
+        "msg": "The same account was passed more than once"
+      },
+      {
+        "code": 6048,
+        "name": "machineAlreadyMigrated",
+        "msg": "Machine is already in the current layout"
+      },
+      {
+        "code": 6049,
+        "name": "plantDataMissing",
+        "msg": "The plant's data header and first data page are required to migrate its readings"
+      }
+    ],
+    "types": [
+      {
+        "name": "accessReceipt",
+        "docs": [
+          "Proof that `buyer` purchased entry `entry_index` of the `data` account.",
+          "Derived from `[b\"receipt\", data, entry_index (le bytes), buyer]`."
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "buyer",
+              "type": "pubkey"
+            },
+            {
+              "name": "data",
+              "type": "pubkey"
+            },
+            {
+              "name": "entryIndex",
+              "type": "u64"
+            },
+            {
+              "name": "pricePaid",
+              "type": "u64"
+            },
+            {
+              "name": "purchasedAt",
+              "type": "i64"
+            },
+            {
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
+      },
+      {
+        "name": "batchReading",
+        "docs": [
+          "One buffered reading in `upload_data_batch`"
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "measuredAt",
+              "type": "i64"
+            },
+            {
+              "name": "temperature",
+              "type": "i16"
+            },
+            {
+              "name": "humidity",
+              "type": "u16"
+            },
+            {
+              "name": "image",
+              "type": {
+                "option": {
+                  "defined": {
+                    "name": "imageRef"
+                  }
+                }
+              }
+            },
+            {
+              "name": "metrics",
+              "type": "bytes"
+            }
+          ]
+        }
+      },
+      {
+        "name": "cluster",
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "authority",
+              "type": "pubkey"
+            },
+            {
+              "name": "machineCount",
+              "type": "u64"
+            },
+            {
+              "name": "dataRequestCount",
+              "type": "u64"
+            },
+            {
+              "name": "plantCount",
+              "type": "u64"
+            },
+            {
+              "name": "rewardMint",
+              "type": "pubkey"
+            },
+            {
+              "name": "rewardAuthorityBump",
+              "type": "u8"
+            },
+            {
+              "name": "protocolFeeBps",
+              "type": "u16"
+            },
+            {
+              "name": "uploadPolicy",
+              "type": {
+                "defined": {
+                  "name": "uploadPolicy"
+                }
+              }
+            },
+            {
+              "name": "sensorRanges",
+              "type": {
+                "defined": {
+                  "name": "sensorRanges"
+                }
+              }
+            },
+            {
+              "name": "delegation",
+              "type": {
+                "defined": {
+                  "name": "delegationRecord"
+                }
+              }
+            },
+            {
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
+      },
+      {
+        "name": "dataEntry",
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "timestamp",
+              "type": "i64"
+            },
+            {
+              "name": "temperature",
+              "docs": [
+                "Hundredths of a degree Celsius"
+              ],
+              "type": "i16"
+            },
+            {
+              "name": "humidity",
+              "docs": [
+                "Relative humidity in tenths of a percent"
+              ],
+              "type": "u16"
+            },
+            {
+              "name": "image",
+              "type": {
+                "option": {
+                  "defined": {
+                    "name": "imageRef"
+                  }
+                }
+              }
+            },
+            {
+              "name": "usedCount",
+              "type": "u64"
+            },
+            {
+              "name": "quality",
+              "type": {
+                "defined": {
+                  "name": "dataQuality"
+                }
+              }
+            },
+            {
+              "name": "metrics",
+              "docs": [
+                "Extra metrics encoded with `metrics::encode_metrics`"
+              ],
+              "type": "bytes"
+            },
+            {
+              "name": "measuredAt",
+              "docs": [
+                "Device-supplied measurement time, when it differs from `timestamp`"
+              ],
+              "type": {
+                "option": "i64"
+              }
+            }
+          ]
+        }
+      },
+      {
+        "name": "dataQuality",
+        "docs": [
+          "Quality flag stored with every reading so buyers can filter junk"
+        ],
+        "type": {
+          "kind": "enum",
+          "variants": [
+            {
+              "name": "ok"
+            },
+            {
+              "name": "outOfRange"
+            },
+            {
+              "name": "suspect"
+            }
+          ]
+        }
+      },
+      {
+        "name": "delegationRecord",
+        "docs": [
+          "Ephemeral rollup settings an account was last delegated with, kept for",
+          "auditing. Stays at its default until the account is first delegated."
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "validator",
+              "docs": [
+                "Validator the account is pinned to, `None` for any validator"
+              ],
+              "type": {
+                "option": "pubkey"
+              }
+            },
+            {
+              "name": "commitFrequencyMs",
+              "type": "u32"
+            },
+            {
+              "name": "delegatedAt",
+              "type": "i64"
+            }
+          ]
+        }
+      },
+      {
+        "name": "entityKind",
+        "type": {
+          "kind": "enum",
+          "variants": [
+            {
+              "name": "machine"
+            },
+            {
+              "name": "plant"
+            }
+          ]
+        }
+      },
+      {
+        "name": "imageMime",
+        "type": {
+          "kind": "enum",
+          "variants": [
+            {
+              "name": "jpeg"
+            },
+            {
+              "name": "png"
+            },
+            {
+              "name": "webp"
+            }
+          ]
+        }
+      },
+      {
+        "name": "imageRef",
+        "docs": [
+          "Content-addressed reference to an image captured by a machine"
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "storage",
+              "type": {
+                "defined": {
+                  "name": "imageStorage"
+                }
+              }
+            },
+            {
+              "name": "contentHash",
+              "docs": [
+                "SHA-256 of the image bytes"
+              ],
+              "type": {
+                "array": [
+                  "u8",
+                  32
+                ]
+              }
+            },
+            {
+              "name": "mime",
+              "type": {
+                "defined": {
+                  "name": "imageMime"
+                }
+              }
+            },
+            {
+              "name": "size",
+              "docs": [
+                "Image size in bytes"
+              ],
+              "type": "u32"
+            }
+          ]
+        }
+      },
+      {
+        "name": "imageStorage",
+        "docs": [
+          "Where an image is stored. The locator alone is not trusted; buyers verify",
+          "the downloaded bytes against `ImageRef.content_hash`."
+        ],
+        "type": {
+          "kind": "enum",
+          "variants": [
+            {
+              "name": "ipfs",
+              "fields": [
+                "string"
+              ]
+            },
+            {
+              "name": "arweave",
+              "fields": [
+                {
+                  "array": [
+                    "u8",
+                    32
+                  ]
+                }
+              ]
+            },
+            {
+              "name": "https",
+              "fields": [
+                "string"
+              ]
+            }
+          ]
+        }
+      },
+      {
+        "name": "ioTData",
+        "docs": [
+          "Header for the readings of one machine/plant pair. The entries themselves",
+          "live in `IoTDataPage` accounts derived from",
+          "`[b\"data_page\", data, page_number (le bytes)]`."
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "machine",
+              "type": "pubkey"
+            },
+            {
+              "name": "plant",
+              "type": "pubkey"
+            },
+            {
+              "name": "totalEntries",
+              "type": "u64"
+            },
+            {
+              "name": "delegation",
+              "type": {
+                "defined": {
+                  "name": "delegationRecord"
+                }
+              }
+            },
+            {
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
+      },
+      {
+        "name": "ioTDataPage",
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "data",
+              "type": "pubkey"
+            },
+            {
+              "name": "pageNumber",
+              "type": "u64"
+            },
+            {
+              "name": "dataEntries",
+              "type": {
+                "vec": {
+                  "defined": {
+                    "name": "dataEntry"
+                  }
+                }
+              }
+            },
+            {
+              "name": "delegation",
+              "type": {
+                "defined": {
+                  "name": "delegationRecord"
+                }
+              }
+            },
+            {
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
       },
       {
-        "code": 6002,
-        "name": "machineNotActive",
-        "msg": "Machine is not active"
+        "name": "metricSpec",
+        "docs": [
+          "Declares how a machine's extra metric is interpreted"
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "metricId",
+              "type": "u8"
+            },
+            {
+              "name": "unit",
+              "docs": [
+                "Unit code, see the constants in the `metrics` module"
+              ],
+              "type": "u8"
+            },
+            {
+              "name": "scale",
+              "docs": [
+                "Decimal exponent applied to raw values, e.g. -2 for hundredths"
+              ],
+              "type": "i8"
+            }
+          ]
+        }
       },
       {
-        "code": 6003,
-        "name": "noRewardsAvailable",
-        "msg": "No rewards available to claim"
+        "name": "plantData",
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "creator",
+              "type": "pubkey"
+            },
+            {
+              "name": "plantName",
+              "type": "string"
+            },
+            {
+              "name": "dataCount",
+              "type": "u64"
+            },
+            {
+              "name": "imageCount",
+              "type": "u64"
+            },
+            {
+              "name": "creationTimestamp",
+              "type": "i64"
+            },
+            {
+              "name": "lastUpdateTimestamp",
+              "type": "i64"
+            },
+            {
+              "name": "lastMeasuredAt",
+              "docs": [
+                "Latest `DataEntry::reading_time` uploaded for this plant"
+              ],
+              "type": "i64"
+            },
+            {
+              "name": "machine",
+              "type": "pubkey"
+            },
+            {
+              "name": "delegation",
+              "type": {
+                "defined": {
+                  "name": "delegationRecord"
+                }
+              }
+            },
+            {
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
       },
       {
-        "code": 6004,
-        "name": "unregisteredPlant",
-        "msg": "Unregistered plant"
+        "name": "registryEntry",
+        "docs": [
+          "One registered machine or plant. Entries are derived from",
+          "`[b\"machine_entry\", index (le bytes)]` or `[b\"plant_entry\", index (le bytes)]`",
+          "where `index` runs from 0 to `Cluster.machine_count` / `Cluster.plant_count`.",
+          "",
+          "Clients can also enumerate them with `getProgramAccounts` filters:",
+          "- `memcmp` at offset 0: the `RegistryEntry` account discriminator",
+          "- `memcmp` at offset 8: `kind` (0 = machine, 1 = plant)",
+          "- `memcmp` at offset 17: `owner`, to list a single farmer's entities"
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "kind",
+              "type": {
+                "defined": {
+                  "name": "entityKind"
+                }
+              }
+            },
+            {
+              "name": "index",
+              "type": "u64"
+            },
+            {
+              "name": "owner",
+              "type": "pubkey"
+            },
+            {
+              "name": "account",
+              "type": "pubkey"
+            },
+            {
+              "name": "name",
+              "type": "string"
+            },
+            {
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
       },
       {
-        "code": 6005,
-        "name": "invalidDataEntryIndex",
-        "msg": "Invalid data entry index"
+        "name": "rollupConfig",
+        "docs": [
+          "Ephemeral rollup settings, kept out of the cluster so they stay readable on",
+          "the base layer while the cluster is delegated. Derived from",
+          "`[b\"rollup_config\"]`."
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "authority",
+              "type": "pubkey"
+            },
+            {
+              "name": "maxCommitIntervalSecs",
+              "docs": [
+                "How stale delegated machine state may get before anyone can commit",
+                "it, 0 to only allow owners to commit. Also caps delegation commit",
+                "frequencies."
+              ],
+              "type": "i64"
+            },
+            {
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
       },
       {
-        "code": 6006,
-        "name": "plantNotLinkedToMachine",
-        "msg": "Plant not linked to the specified machine"
-      }
-    ],
-    "types": [
-      {
-        "name": "dataEntry",
+        "name": "sensorPayload",
+        "docs": [
+          "Reading signed by a machine's device key for `upload_signed_data`.",
+          "`timestamp` is the device's measurement time and `image_hash` is the",
+          "`content_hash` of the attached image, or all zeros without an image."
+        ],
         "type": {
           "kind": "struct",
           "fields": [
+            {
+              "name": "machineId",
+              "type": "string"
+            },
+            {
+              "name": "plant",
+              "type": "pubkey"
+            },
             {
               "name": "timestamp",
               "type": "i64"
             },
             {
               "name": "temperature",
-              "type": "f64"
+              "type": "i16"
             },
             {
               "name": "humidity",
-              "type": "f64"
+              "type": "u16"
             },
             {
-              "name": "imageUrl",
+              "name": "imageHash",
               "type": {
-                "option": "string"
+                "array": [
+                  "u8",
+                  32
+                ]
               }
             },
             {
-              "name": "usedCount",
+              "name": "nonce",
               "type": "u64"
+            },
+            {
+              "name": "metrics",
+              "docs": [
+                "Extra metrics encoded with `metrics::encode_metrics`"
+              ],
+              "type": "bytes"
             }
           ]
         }
       },
       {
-        "name": "ioTData",
+        "name": "sensorRanges",
+        "docs": [
+          "Plausible sensor values; readings outside are stored as out of range"
+        ],
         "type": {
           "kind": "struct",
           "fields": [
             {
-              "name": "machine",
-              "type": "pubkey"
+              "name": "minTemperature",
+              "type": "i16"
             },
             {
-              "name": "plant",
+              "name": "maxTemperature",
+              "type": "i16"
+            },
+            {
+              "name": "minHumidity",
+              "type": "u16"
+            },
+            {
+              "name": "maxHumidity",
+              "type": "u16"
+            }
+          ]
+        }
+      },
+      {
+        "name": "sensorSchema",
+        "docs": [
+          "Per-machine sensor schema derived from `[b\"schema\", machine]`"
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "machine",
               "type": "pubkey"
             },
             {
-              "name": "dataEntries",
+              "name": "metrics",
               "type": {
                 "vec": {
                   "defined": {
-                    "name": "dataEntry"
+                    "name": "metricSpec"
                   }
                 }
               }
@@ -739,37 +4531,74 @@ export type AgroxContract = {
         }
       },
       {
-        "name": "plantData",
+        "name": "sessionToken",
+        "docs": [
+          "Short-lived key allowed to sign a machine's uploads in place of its device",
+          "key, e.g. a hot key kept on the device while the machine is delegated.",
+          "Derived from `[b\"session\", machine, session_key]`."
+        ],
         "type": {
           "kind": "struct",
           "fields": [
             {
-              "name": "creator",
+              "name": "machine",
               "type": "pubkey"
             },
             {
-              "name": "plantName",
-              "type": "string"
+              "name": "owner",
+              "type": "pubkey"
             },
             {
-              "name": "dataCount",
-              "type": "u64"
+              "name": "sessionKey",
+              "type": "pubkey"
             },
             {
-              "name": "imageCount",
-              "type": "u64"
+              "name": "createdAt",
+              "type": "i64"
             },
             {
-              "name": "creationTimestamp",
+              "name": "expiresAt",
               "type": "i64"
             },
             {
-              "name": "lastUpdateTimestamp",
-              "type": "i64"
+              "name": "scope",
+              "docs": [
+                "Bitmask of `SCOPE_*` flags"
+              ],
+              "type": "u8"
             },
             {
-              "name": "machine",
-              "type": "pubkey"
+              "name": "bump",
+              "type": "u8"
+            }
+          ]
+        }
+      },
+      {
+        "name": "throttleMode",
+        "docs": [
+          "What happens to uploads that arrive inside the minimum interval"
+        ],
+        "type": {
+          "kind": "enum",
+          "variants": [
+            {
+              "name": "reject"
+            },
+            {
+              "name": "noReward"
+            }
+          ]
+        }
+      },
+      {
+        "name": "treasury",
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "feesCollected",
+              "type": "u64"
             },
             {
               "name": "bump",
@@ -777,6 +4606,54 @@ export type AgroxContract = {
             }
           ]
         }
+      },
+      {
+        "name": "uploadPolicy",
+        "docs": [
+          "Cluster-wide limits on how often machines can earn upload rewards"
+        ],
+        "type": {
+          "kind": "struct",
+          "fields": [
+            {
+              "name": "minUploadIntervalSecs",
+              "type": "i64"
+            },
+            {
+              "name": "minImageIntervalSecs",
+              "type": "i64"
+            },
+            {
+              "name": "epochRewardCap",
+              "docs": [
+                "Maximum upload rewards per machine per epoch, 0 for no cap"
+              ],
+              "type": "u64"
+            },
+            {
+              "name": "throttleMode",
+              "type": {
+                "defined": {
+                  "name": "throttleMode"
+                }
+              }
+            },
+            {
+              "name": "maxClockSkewSecs",
+              "docs": [
+                "How far ahead of the chain clock a device timestamp may be"
+              ],
+              "type": "i64"
+            },
+            {
+              "name": "maxBackfillSecs",
+              "docs": [
+                "How old a backfilled reading may be, 0 for no limit"
+              ],
+              "type": "i64"
+            }
+          ]
+        }
       }
     ]
   };
@@ -0,0 +1,148 @@
+//! Compact variable-length encoding for sensor metrics beyond temperature and
+//! humidity. Each reading is a metric id byte followed by its raw value as a
+//! zigzag LEB128 varint, so small values take a single byte. Machines declare
+//! the unit and decimal scale of every metric id in their `SensorSchema`.
+
+/// Well-known metric ids. Machines may declare any other id in their schema.
+pub const SOIL_MOISTURE: u8 = 1;
+pub const PH: u8 = 2;
+pub const LIGHT: u8 = 3;
+pub const CO2: u8 = 4;
+pub const ELECTRICAL_CONDUCTIVITY: u8 = 5;
+
+/// Well-known unit codes for `MetricSpec.unit`
+pub const UNIT_PERCENT: u8 = 1;
+pub const UNIT_PH: u8 = 2;
+pub const UNIT_LUX: u8 = 3;
+pub const UNIT_PPM: u8 = 4;
+pub const UNIT_MICROSIEMENS_PER_CM: u8 = 5;
+
+/// A varint never needs more than 10 bytes for a 64-bit value
+const MAX_VARINT_BYTES: usize = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MetricValue {
+    pub metric_id: u8,
+    /// Raw value; the real value is `value * 10^scale` from the schema
+    pub value: i64,
+}
+
+pub fn encode_metrics(values: &[MetricValue]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    for metric in values {
+        encoded.push(metric.metric_id);
+        let mut zigzag = ((metric.value << 1) ^ (metric.value >> 63)) as u64;
+        loop {
+            let byte = (zigzag & 0x7f) as u8;
+            zigzag >>= 7;
+            if zigzag == 0 {
+                encoded.push(byte);
+                break;
+            }
+            encoded.push(byte | 0x80);
+        }
+    }
+    encoded
+}
+
+/// Decode readings produced by `encode_metrics`. Returns `None` if the
+/// encoding is truncated or a varint is malformed.
+pub fn decode_metrics(mut encoded: &[u8]) -> Option<Vec<MetricValue>> {
+    let mut values = Vec::new();
+    while let Some((&metric_id, rest)) = encoded.split_first() {
+        let mut zigzag: u64 = 0;
+        let mut length = 0;
+        loop {
+            let byte = *rest.get(length)?;
+            if length == MAX_VARINT_BYTES - 1 && byte > 1 {
+                return None;
+            }
+            zigzag |= ((byte & 0x7f) as u64) << (7 * length);
+            length += 1;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let value = (zigzag >> 1) as i64 ^ -((zigzag & 1) as i64);
+        values.push(MetricValue { metric_id, value });
+        encoded = &rest[length..];
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(values: &[MetricValue]) {
+        assert_eq!(decode_metrics(&encode_metrics(values)).as_deref(), Some(values));
+    }
+
+    #[test]
+    fn round_trips_edge_values() {
+        for value in [0, -1, 1, 63, -64, 64, -65, i64::MIN, i64::MIN + 1, i64::MAX, i64::MAX - 1] {
+            round_trip(&[MetricValue { metric_id: PH, value }]);
+        }
+    }
+
+    #[test]
+    fn round_trips_several_metrics() {
+        round_trip(&[]);
+        round_trip(&[
+            MetricValue { metric_id: SOIL_MOISTURE, value: 4_215 },
+            MetricValue { metric_id: PH, value: -1 },
+            MetricValue { metric_id: LIGHT, value: i64::MAX },
+            MetricValue { metric_id: CO2, value: i64::MIN },
+            MetricValue { metric_id: ELECTRICAL_CONDUCTIVITY, value: 0 },
+        ]);
+    }
+
+    #[test]
+    fn small_values_take_one_byte() {
+        assert_eq!(encode_metrics(&[MetricValue { metric_id: PH, value: 0 }]), [PH, 0]);
+        assert_eq!(encode_metrics(&[MetricValue { metric_id: PH, value: -1 }]), [PH, 1]);
+        assert_eq!(encode_metrics(&[MetricValue { metric_id: PH, value: 63 }]), [PH, 126]);
+    }
+
+    #[test]
+    fn extremes_take_the_maximum_varint_length() {
+        for value in [i64::MIN, i64::MAX] {
+            let encoded = encode_metrics(&[MetricValue { metric_id: PH, value }]);
+            assert_eq!(encoded.len(), 1 + MAX_VARINT_BYTES);
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_varints() {
+        // Metric id without a value
+        assert_eq!(decode_metrics(&[PH]), None);
+        // Continuation bit set on the last byte
+        assert_eq!(decode_metrics(&[PH, 0x80]), None);
+
+        let encoded = encode_metrics(&[MetricValue { metric_id: PH, value: i64::MIN }]);
+        for len in 1..encoded.len() {
+            assert_eq!(decode_metrics(&encoded[..len]), None);
+        }
+    }
+
+    #[test]
+    fn rejects_varints_longer_than_ten_bytes() {
+        let mut encoded = vec![PH];
+        encoded.extend([0xff; MAX_VARINT_BYTES]);
+        encoded.push(0x01);
+        assert_eq!(decode_metrics(&encoded), None);
+
+        // A tenth byte carrying more than the final bit overflows 64 bits
+        let mut encoded = vec![PH];
+        encoded.extend([0xff; MAX_VARINT_BYTES - 1]);
+        encoded.push(0x02);
+        assert_eq!(decode_metrics(&encoded), None);
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut encoded = encode_metrics(&[MetricValue { metric_id: PH, value: 700 }]);
+        encoded.push(CO2);
+        assert_eq!(decode_metrics(&encoded), None);
+    }
+}
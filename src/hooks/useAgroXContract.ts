@@ -1,6 +1,6 @@
 'use client'
 
-import { useEffect, useState, useCallback } from 'react';
+import { useEffect, useState, useCallback, useMemo } from 'react';
 import * as anchor from '@coral-xyz/anchor';
 import { PublicKey, Connection } from '@solana/web3.js';
 import { useAnchorWallet } from '@solana/wallet-adapter-react';
@@ -54,14 +54,40 @@ type IoTData = {
   publicKey: PublicKey; // Added for easier reference
 };
 
+type UploadPolicy = {
+  minUploadIntervalSecs: anchor.BN;
+  minImageIntervalSecs: anchor.BN;
+  epochRewardCap: anchor.BN;
+  throttleMode: { reject: Record<string, never> } | { noReward: Record<string, never> };
+  maxClockSkewSecs: anchor.BN;
+  maxBackfillSecs: anchor.BN;
+};
+
+type SensorRanges = {
+  minTemperature: number;
+  maxTemperature: number;
+  minHumidity: number;
+  maxHumidity: number;
+};
+
+type DelegationRecord = {
+  validator: PublicKey | null;
+  commitFrequencyMs: number;
+  delegatedAt: anchor.BN;
+};
+
+// The cluster no longer counts uploads itself, see `totalDataUploads` below
 type Cluster = {
   authority: PublicKey;
   machineCount: anchor.BN;
-  totalDataUploads: anchor.BN;
   dataRequestCount: anchor.BN;
   plantCount: anchor.BN;
-  machines: [string, PublicKey][];
-  plants: [string, PublicKey][];
+  rewardMint: PublicKey;
+  rewardAuthorityBump: number;
+  protocolFeeBps: number;
+  uploadPolicy: UploadPolicy;
+  sensorRanges: SensorRanges;
+  delegation: DelegationRecord;
   bump: number;
   publicKey: PublicKey; // Added for easier reference
 };
@@ -102,6 +128,12 @@ export function useAgroXContract() {
   const [latestImage, setLatestImage] = useState<string | null>(null);
   const [isFetchingSensorData, setIsFetchingSensorData] = useState(false);
 
+  // Every upload is counted on its plant, so the plants add up to the total
+  const totalDataUploads = useMemo(
+    () => plants.reduce((total, plant) => total.add(plant.dataCount), new anchor.BN(0)),
+    [plants]
+  );
+
   const getProvider = () => {
     if (!wallet) {
       throw new Error("Wallet not connected");
@@ -702,6 +734,7 @@ export function useAgroXContract() {
   return {
     // State
     cluster,
+    totalDataUploads,
     machines,
     plants,
     iotData,